@@ -0,0 +1,89 @@
+use crate::update::{ParticleSpawnerState, ParticleStore};
+use bevy_camera::Camera;
+use bevy_ecs::{
+    resource::Resource,
+    system::{Query, ResMut},
+};
+use bevy_math::Vec3Swizzles;
+use bevy_reflect::Reflect;
+use bevy_transform::components::GlobalTransform;
+
+/// Global cap on the living particles of all spawners.
+///
+/// Once more particles are alive than the budget allows, every spawner
+/// emits less. Distant and low priority spawners are throttled the most,
+/// see `ParticleSpawnerState::priority`. Existing particles are never removed
+/// and `OneShot` spawners always emit in full.
+#[derive(Resource, Clone, Debug, Reflect)]
+pub struct ParticleBudget {
+    /// living particles across all spawners, before emission is reduced
+    pub max_particles: u32,
+    /// distance from the closest camera, at which
+    /// the importance of a spawner halves
+    pub lod_distance: f32,
+    /// living particles, counted on the last update
+    pub live_particles: u32,
+}
+
+impl Default for ParticleBudget {
+    fn default() -> Self {
+        Self {
+            max_particles: u32::MAX,
+            lod_distance: 1000.,
+            live_particles: 0,
+        }
+    }
+}
+
+impl ParticleBudget {
+    pub fn new(max_particles: u32) -> Self {
+        Self {
+            max_particles,
+            ..Default::default()
+        }
+    }
+
+    /// the share of new particles a spawner emits. 1 within budget,
+    /// over budget a spawner of importance 1 is cut by the overload ratio,
+    /// less important spawners are cut harder.
+    fn emission_scale(&self, importance: f32) -> f32 {
+        if self.live_particles <= self.max_particles {
+            return 1.;
+        }
+        if importance <= 0. {
+            return 0.;
+        }
+
+        let ratio = self.max_particles as f32 / self.live_particles as f32;
+        ratio.powf(importance.recip())
+    }
+}
+
+pub(crate) fn update_particle_budget(
+    mut budget: ResMut<ParticleBudget>,
+    mut spawners: Query<(&ParticleStore, &mut ParticleSpawnerState, &GlobalTransform)>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    budget.live_particles = spawners
+        .iter()
+        .map(|(store, ..)| store.len() as u32)
+        .fold(0, u32::saturating_add);
+
+    let lod_distance = budget.lod_distance.max(f32::EPSILON);
+    for (_, mut state, transform) in spawners.iter_mut() {
+        let distance = cameras
+            .iter()
+            .filter(|(camera, _)| camera.is_active)
+            .map(|(_, camera)| {
+                camera
+                    .translation()
+                    .xy()
+                    .distance(transform.translation().xy())
+            })
+            .reduce(f32::min)
+            .unwrap_or_default();
+
+        let importance = state.priority / (1. + distance / lod_distance);
+        state.emission_scale = budget.emission_scale(importance);
+    }
+}
@@ -0,0 +1,213 @@
+use bevy_ecs::{
+    component::Component,
+    reflect::ReflectComponent,
+    resource::Resource,
+    system::{Query, ResMut},
+};
+use bevy_math::{Vec2, Vec3Swizzles};
+use bevy_reflect::Reflect;
+use bevy_transform::components::{GlobalTransform, Transform};
+
+/// offset from the surface, a particle is placed at after a bounce
+const SURFACE_OFFSET: f32 = 0.01;
+
+/// A collider shape particles can bounce off.
+/// Positioned, rotated and scaled by the entities `GlobalTransform`.
+/// Only effects with `collision` settings are affected.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+#[require(Transform)]
+pub enum ParticleCollider {
+    /// infinite line through the entity, particles collide from
+    /// the side its local up axis is facing.
+    Plane,
+    /// circle with a radius
+    Circle(f32),
+    /// rectangle with a half size
+    Rect(Vec2),
+}
+
+/// The result of a collision query.
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleHit {
+    pub point: Vec2,
+    pub normal: Vec2,
+}
+
+/// Implement this to collide particles with your own
+/// physics world, tilemaps or anything else.
+pub trait ParticleCollisionQuery: Send + Sync + 'static {
+    /// returns the first hit, when a particle moves from `start` to `end`
+    fn cast(&self, start: Vec2, end: Vec2) -> Option<ParticleHit>;
+}
+
+/// Holds every collider particles can hit. The collider components
+/// are collected each frame. Custom queries are kept until removed.
+#[derive(Resource, Default)]
+pub struct ParticleColliders {
+    colliders: Vec<(ParticleCollider, GlobalTransform)>,
+    pub queries: Vec<Box<dyn ParticleCollisionQuery>>,
+}
+
+impl ParticleColliders {
+    /// adds a custom collision query
+    pub fn add_query(&mut self, query: impl ParticleCollisionQuery) {
+        self.queries.push(Box::new(query));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colliders.is_empty() && self.queries.is_empty()
+    }
+
+    /// returns the closest hit of all colliders
+    pub fn cast(&self, start: Vec2, end: Vec2) -> Option<ParticleHit> {
+        self.colliders
+            .iter()
+            .filter_map(|(collider, transform)| collider.cast(transform, start, end))
+            .chain(
+                self.queries
+                    .iter()
+                    .filter_map(|query| query.cast(start, end)),
+            )
+            .min_by(|a, b| {
+                a.point
+                    .distance_squared(start)
+                    .total_cmp(&b.point.distance_squared(start))
+            })
+    }
+}
+
+impl ParticleCollider {
+    fn cast(&self, transform: &GlobalTransform, start: Vec2, end: Vec2) -> Option<ParticleHit> {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let center = translation.xy();
+
+        match self {
+            ParticleCollider::Plane => {
+                let normal = (rotation * bevy_math::Vec3::Y).xy().normalize_or_zero();
+                let d_start = (start - center).dot(normal);
+                let d_end = (end - center).dot(normal);
+
+                if d_start < 0. || d_end >= 0. {
+                    return None;
+                }
+
+                let t = d_start / (d_start - d_end);
+                Some(ParticleHit {
+                    point: start.lerp(end, t),
+                    normal,
+                })
+            }
+            ParticleCollider::Circle(radius) => {
+                let radius = radius * scale.x.abs();
+                let from_center = start - center;
+                let c = from_center.length_squared() - radius * radius;
+
+                // starts inside, let it escape
+                if c < 0. {
+                    return None;
+                }
+
+                let direction = end - start;
+                let a = direction.length_squared();
+                let b = from_center.dot(direction);
+                let discriminant = b * b - a * c;
+
+                if a <= 0. || discriminant < 0. {
+                    return None;
+                }
+
+                let t = (-b - discriminant.sqrt()) / a;
+                if !(0. ..=1.).contains(&t) {
+                    return None;
+                }
+
+                let point = start + direction * t;
+                Some(ParticleHit {
+                    point,
+                    normal: (point - center).normalize_or_zero(),
+                })
+            }
+            ParticleCollider::Rect(half_size) => {
+                let half_size = *half_size * scale.xy().abs();
+                let inverse = rotation.inverse();
+                let local_start = (inverse * (start - center).extend(0.)).xy();
+                let local_end = (inverse * (end - center).extend(0.)).xy();
+
+                // starts inside, let it escape
+                if local_start.abs().cmplt(half_size).all() {
+                    return None;
+                }
+
+                let direction = local_end - local_start;
+                let mut t_enter = 0.0_f32;
+                let mut t_exit = 1.0_f32;
+                let mut normal = Vec2::ZERO;
+
+                for axis in 0..2 {
+                    if direction[axis].abs() < f32::EPSILON {
+                        if local_start[axis].abs() > half_size[axis] {
+                            return None;
+                        }
+                        continue;
+                    }
+
+                    let t0 = (-half_size[axis] - local_start[axis]) / direction[axis];
+                    let t1 = (half_size[axis] - local_start[axis]) / direction[axis];
+                    let (near, far) = (t0.min(t1), t0.max(t1));
+
+                    if near > t_enter {
+                        t_enter = near;
+                        normal = Vec2::ZERO;
+                        normal[axis] = -direction[axis].signum();
+                    }
+                    t_exit = t_exit.min(far);
+
+                    if t_enter > t_exit {
+                        return None;
+                    }
+                }
+
+                if normal == Vec2::ZERO {
+                    return None;
+                }
+
+                Some(ParticleHit {
+                    point: start.lerp(end, t_enter),
+                    normal: (rotation * normal.extend(0.)).xy(),
+                })
+            }
+        }
+    }
+}
+
+/// returns the corrected position and velocity,
+/// if a particle moving from `start` to `end` hits something.
+pub(crate) fn resolve_collision(
+    colliders: &ParticleColliders,
+    settings: &crate::Collision,
+    start: Vec2,
+    end: Vec2,
+    velocity: Vec2,
+) -> Option<(Vec2, Vec2)> {
+    let hit = colliders.cast(start, end)?;
+
+    let normal_velocity = hit.normal * velocity.dot(hit.normal);
+    let tangent_velocity = velocity - normal_velocity;
+    let velocity = tangent_velocity * (1. - settings.friction.clamp(0., 1.))
+        - normal_velocity * settings.bounce.max(0.);
+
+    Some((hit.point + hit.normal * SURFACE_OFFSET, velocity))
+}
+
+pub(crate) fn collect_colliders(
+    mut particle_colliders: ResMut<ParticleColliders>,
+    colliders: Query<(&ParticleCollider, &GlobalTransform)>,
+) {
+    particle_colliders.colliders.clear();
+    particle_colliders.colliders.extend(
+        colliders
+            .iter()
+            .map(|(collider, transform)| (collider.clone(), *transform)),
+    );
+}
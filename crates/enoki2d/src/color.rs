@@ -5,7 +5,10 @@ use bevy_reflect::TypePath;
 use bevy_render::render_resource::{AsBindGroup, ShaderType};
 use bevy_shader::ShaderRef;
 
-use super::{prelude::Particle2dMaterial, PARTICLE_COLOR_FRAG};
+use super::{
+    prelude::{Particle2dMaterial, ParticleBlendMode},
+    PARTICLE_COLOR_FRAG,
+};
 
 /// simple color material that gets multiplied by
 /// any color, caluclated in the particle effect
@@ -13,6 +16,7 @@ use super::{prelude::Particle2dMaterial, PARTICLE_COLOR_FRAG};
 pub struct ColorParticle2dMaterial {
     #[uniform(0)]
     color: LinearRgba,
+    pub blend_mode: ParticleBlendMode,
 }
 
 #[derive(ShaderType, Asset, TypePath, Clone)]
@@ -25,13 +29,22 @@ impl Default for ColorParticle2dMaterial {
     fn default() -> Self {
         Self {
             color: LinearRgba::WHITE,
+            blend_mode: ParticleBlendMode::Alpha,
         }
     }
 }
 
 impl ColorParticle2dMaterial {
     pub fn new(color: LinearRgba) -> Self {
-        Self { color }
+        Self {
+            color,
+            blend_mode: ParticleBlendMode::Alpha,
+        }
+    }
+
+    pub fn with_blend_mode(mut self, blend_mode: ParticleBlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
     }
 }
 
@@ -39,4 +52,8 @@ impl Particle2dMaterial for ColorParticle2dMaterial {
     fn fragment_shader() -> ShaderRef {
         PARTICLE_COLOR_FRAG.into()
     }
+
+    fn blend_mode(&self) -> ParticleBlendMode {
+        self.blend_mode
+    }
 }
@@ -1,7 +1,7 @@
 use bevy_color::{Color, ColorToComponents, LinearRgba};
 use bevy_math::{
     curve::{EaseFunction, EasingCurve},
-    Curve, FloatExt,
+    Curve, FloatExt, Vec2,
 };
 use serde::{Deserialize, Serialize};
 
@@ -93,6 +93,12 @@ impl LerpThat<f32> for f32 {
     }
 }
 
+impl LerpThat<Vec2> for Vec2 {
+    fn lerp_that(self, right: Vec2, val: f32) -> Vec2 {
+        self.lerp(right, val)
+    }
+}
+
 impl LerpThat<Color> for Color {
     fn lerp_that(self, right: Color, val: f32) -> Color {
         let out = self
@@ -0,0 +1,199 @@
+use crate::{
+    mask::ParticleImageMasks, mesh::ParticleMeshShapes, polygon::ParticlePolygons,
+    values::ParticleRng, EmissionMode, EmissionShape,
+};
+use bevy_color::LinearRgba;
+use bevy_math::Vec2;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+/// a point on the emission shape, in spawner space
+pub(crate) struct EmissionPoint {
+    pub position: Vec2,
+    /// outward normal, zero for a point
+    pub normal: Vec2,
+    /// color of the emitting pixel
+    pub color: Option<LinearRgba>,
+}
+
+impl From<(Vec2, Vec2)> for EmissionPoint {
+    fn from((position, normal): (Vec2, Vec2)) -> Self {
+        Self {
+            position,
+            normal,
+            color: None,
+        }
+    }
+}
+
+/// samples a uniform point on the shape. `None`, if the
+/// shape has nothing to emit from, like an image still loading.
+pub(crate) fn sample(
+    shape: &EmissionShape,
+    mode: EmissionMode,
+    masks: &ParticleImageMasks,
+    meshes: &ParticleMeshShapes,
+    polygons: &ParticlePolygons,
+    rng: &mut ParticleRng,
+) -> Option<EmissionPoint> {
+    let edge = mode == EmissionMode::Edge;
+    let point = match shape {
+        EmissionShape::Point => (Vec2::ZERO, Vec2::ZERO),
+        EmissionShape::Circle(radius) => annulus(0., *radius, 0., TAU, edge, rng),
+        EmissionShape::Ring { inner, outer } => annulus(*inner, *outer, 0., TAU, edge, rng),
+        EmissionShape::Arc { radius, start, end } => annulus(0., *radius, *start, *end, edge, rng),
+        EmissionShape::Rect(size) => rect(*size, edge, rng),
+        EmissionShape::Line(start, end) => (
+            start.lerp(*end, rng.random::<f32>()),
+            (*end - *start).perp().normalize_or_zero(),
+        ),
+        EmissionShape::Polygon(points) => {
+            let triangles = polygons.get(points).filter(|_| !edge);
+            match triangles.and_then(|data| triangle(&data.triangles, &data.areas, rng)) {
+                Some(position) => {
+                    let center = points.iter().sum::<Vec2>() / points.len() as f32;
+                    (position, (position - center).normalize_or_zero())
+                }
+                None => polygon_edge(points, rng),
+            }
+        }
+        EmissionShape::Image(mask) => {
+            let pixel = masks.get(mask)?.sample(mask, edge, rng)?;
+
+            // anywhere inside the pixel, y is up
+            let jitter = Vec2::new(rng.random::<f32>(), -rng.random::<f32>());
+            return Some(EmissionPoint {
+                position: (pixel.position + jitter) * mask.pixel_size,
+                normal: pixel.normal,
+                color: mask.use_color.then_some(pixel.color),
+            });
+        }
+        EmissionShape::Mesh(shape) => {
+            let data = meshes.get(shape)?;
+            let (position, normal) = match edge && !data.edges.is_empty() {
+                true => {
+                    let t = rng.random::<f32>() * data.lengths.last()?;
+                    let index = data.lengths.partition_point(|length| *length < t);
+                    let (a, b, normal) = *data.edges.get(index)?;
+                    (a.lerp(b, rng.random::<f32>()), normal)
+                }
+                false => (triangle(&data.triangles, &data.areas, rng)?, Vec2::ZERO),
+            };
+            (
+                position * shape.scale,
+                (normal * shape.scale.signum()).normalize_or_zero(),
+            )
+        }
+    };
+    Some(point.into())
+}
+
+/// false, while the shape waits for its image or mesh
+pub(crate) fn ready(
+    shape: &EmissionShape,
+    masks: &ParticleImageMasks,
+    meshes: &ParticleMeshShapes,
+) -> bool {
+    match shape {
+        EmissionShape::Image(mask) => masks.get(mask).is_some(),
+        EmissionShape::Mesh(shape) => meshes.get(shape).is_some(),
+        _ => true,
+    }
+}
+
+fn annulus(
+    inner: f32,
+    outer: f32,
+    start: f32,
+    end: f32,
+    edge: bool,
+    rng: &mut ParticleRng,
+) -> (Vec2, Vec2) {
+    let angle = start + (end - start) * rng.random::<f32>();
+    let normal = Vec2::from_angle(angle);
+
+    if edge {
+        // the circumference grows with the radius
+        let inner_weight = inner / (inner + outer).max(f32::EPSILON);
+        return match rng.random::<f32>() < inner_weight {
+            true => (normal * inner, -normal),
+            false => (normal * outer, normal),
+        };
+    }
+
+    // the area grows with the squared radius
+    let (inner_sq, outer_sq) = (inner * inner, outer * outer);
+    let radius = (inner_sq + (outer_sq - inner_sq) * rng.random::<f32>()).sqrt();
+    (normal * radius, normal)
+}
+
+fn rect(size: Vec2, edge: bool, rng: &mut ParticleRng) -> (Vec2, Vec2) {
+    let half = size * 0.5;
+
+    if !edge {
+        let position = (Vec2::new(rng.random::<f32>(), rng.random::<f32>()) - 0.5) * size;
+        return (position, position.normalize_or_zero());
+    }
+
+    // walk the perimeter clockwise, starting top left
+    let mut t = rng.random::<f32>() * 2. * (size.x + size.y);
+    if t < size.x {
+        return (Vec2::new(-half.x + t, half.y), Vec2::Y);
+    }
+    t -= size.x;
+    if t < size.y {
+        return (Vec2::new(half.x, half.y - t), Vec2::X);
+    }
+    t -= size.y;
+    if t < size.x {
+        return (Vec2::new(half.x - t, -half.y), Vec2::NEG_Y);
+    }
+    t -= size.x;
+    (Vec2::new(-half.x, -half.y + t), Vec2::NEG_X)
+}
+
+/// a uniform point on the triangles, weighted by their running total `areas`
+fn triangle(triangles: &[[Vec2; 3]], areas: &[f32], rng: &mut ParticleRng) -> Option<Vec2> {
+    let t = rng.random::<f32>() * areas.last()?;
+    let index = areas.partition_point(|area| *area < t);
+    let [a, b, c] = *triangles.get(index)?;
+
+    // folds the unit square onto the triangle
+    let (mut u, mut v) = (rng.random::<f32>(), rng.random::<f32>());
+    if u + v > 1. {
+        (u, v) = (1. - u, 1. - v);
+    }
+    Some(a + (b - a) * u + (c - a) * v)
+}
+
+fn polygon_edge(points: &[Vec2], rng: &mut ParticleRng) -> (Vec2, Vec2) {
+    match points.len() {
+        0 => return (Vec2::ZERO, Vec2::ZERO),
+        1 => return (points[0], Vec2::ZERO),
+        _ => (),
+    }
+
+    let edges = || {
+        points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| (*a, *b))
+    };
+
+    // counter clockwise polygons have a positive area
+    let winding = edges().map(|(a, b)| a.perp_dot(b)).sum::<f32>().signum();
+    let perimeter = edges().map(|(a, b)| a.distance(b)).sum::<f32>();
+
+    let mut t = rng.random::<f32>() * perimeter;
+    let mut last = (points[0], Vec2::ZERO);
+    for (a, b) in edges() {
+        let length = a.distance(b);
+        last = (b, (a - b).perp().normalize_or_zero() * winding);
+        if t <= length {
+            let position = a.lerp(b, t / length.max(f32::EPSILON));
+            return (position, last.1);
+        }
+        t -= length;
+    }
+    last
+}
@@ -0,0 +1,175 @@
+use bevy_ecs::{
+    component::Component,
+    reflect::ReflectComponent,
+    resource::Resource,
+    system::{Query, ResMut},
+};
+use bevy_math::{Vec2, Vec3, Vec3Swizzles};
+use bevy_reflect::{prelude::ReflectDefault, Reflect};
+use bevy_transform::components::{GlobalTransform, Transform};
+
+/// A force field placed in the world. Bends the particles
+/// of every spawner, that shares one of its `layers`.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+#[require(Transform)]
+pub struct ParticleForceField {
+    pub kind: ForceFieldKind,
+    /// reach of the field in world units. Reaches everywhere if `None`
+    pub radius: Option<f32>,
+    /// how the force fades out towards the `radius`
+    pub falloff: ForceFalloff,
+    /// bit mask, matched against the `ParticleForceLayers` of a spawner
+    pub layers: u32,
+}
+
+#[derive(Clone, Debug, Reflect)]
+pub enum ForceFieldKind {
+    /// pulls particles towards the field, pushes them away
+    /// with a negative strength. Gets stronger the closer they are.
+    Point { strength: f32, min_distance: f32 },
+    /// swirls particles around the field,
+    /// counter clockwise with a positive strength.
+    Vortex { strength: f32 },
+    /// pushes particles in a direction, rotated with the field.
+    Wind { direction: Vec2, strength: f32 },
+}
+
+#[derive(Clone, Copy, Debug, Default, Reflect, PartialEq)]
+pub enum ForceFalloff {
+    /// full force up to the radius
+    #[default]
+    None,
+    /// fades linear to zero at the radius
+    Linear,
+    /// fades smooth to zero at the radius
+    Smooth,
+}
+
+/// The force layers a spawner belongs to. Spawners
+/// without it are affected by every force field.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct ParticleForceLayers(pub u32);
+
+impl Default for ParticleForceLayers {
+    fn default() -> Self {
+        Self(u32::MAX)
+    }
+}
+
+impl ParticleForceField {
+    /// attractor, or repulsor with a negative strength
+    pub fn point(strength: f32) -> Self {
+        Self::new(ForceFieldKind::Point {
+            strength,
+            min_distance: 5.,
+        })
+    }
+
+    pub fn vortex(strength: f32) -> Self {
+        Self::new(ForceFieldKind::Vortex { strength })
+    }
+
+    pub fn wind(direction: Vec2, strength: f32) -> Self {
+        Self::new(ForceFieldKind::Wind {
+            direction: direction.normalize_or_zero(),
+            strength,
+        })
+    }
+
+    pub fn new(kind: ForceFieldKind) -> Self {
+        Self {
+            kind,
+            radius: None,
+            falloff: ForceFalloff::None,
+            layers: u32::MAX,
+        }
+    }
+
+    pub fn with_radius(mut self, radius: f32, falloff: ForceFalloff) -> Self {
+        self.radius = Some(radius);
+        self.falloff = falloff;
+        self
+    }
+
+    pub fn with_layers(mut self, layers: u32) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    fn force(&self, transform: &GlobalTransform, position: Vec2) -> Vec2 {
+        let center = transform.translation().xy();
+        let to_center = center - position;
+        let distance = to_center.length();
+
+        let fade = match self.radius {
+            Some(radius) if distance >= radius => return Vec2::ZERO,
+            Some(radius) => {
+                let t = 1. - distance / radius.max(f32::EPSILON);
+                match self.falloff {
+                    ForceFalloff::None => 1.,
+                    ForceFalloff::Linear => t,
+                    ForceFalloff::Smooth => t * t * (3. - 2. * t),
+                }
+            }
+            None => 1.,
+        };
+
+        let force = match self.kind {
+            ForceFieldKind::Point {
+                strength,
+                min_distance,
+            } => {
+                if distance <= 0. {
+                    return Vec2::ZERO;
+                }
+                let distance_sq = (distance * distance).max(min_distance * min_distance);
+                to_center / distance * strength / distance_sq
+            }
+            ForceFieldKind::Vortex { strength } => {
+                (-to_center).normalize_or_zero().perp() * strength
+            }
+            ForceFieldKind::Wind {
+                direction,
+                strength,
+            } => (transform.rotation() * direction.extend(0.)).xy() * strength,
+        };
+
+        force * fade
+    }
+}
+
+/// Every force field in the world, collected each frame.
+#[derive(Resource, Default)]
+pub struct ParticleForceFields {
+    fields: Vec<(ParticleForceField, GlobalTransform)>,
+}
+
+impl ParticleForceFields {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// sum of all forces at `position`, of the fields in `layers`
+    pub fn force(&self, position: Vec3, layers: u32) -> Vec3 {
+        self.fields
+            .iter()
+            .filter(|(field, _)| field.layers & layers != 0)
+            .map(|(field, transform)| field.force(transform, position.xy()))
+            .sum::<Vec2>()
+            .extend(0.)
+    }
+}
+
+pub(crate) fn collect_force_fields(
+    mut force_fields: ResMut<ParticleForceFields>,
+    fields: Query<(&ParticleForceField, &GlobalTransform)>,
+) {
+    force_fields.fields.clear();
+    force_fields.fields.extend(
+        fields
+            .iter()
+            .map(|(field, transform)| (field.clone(), *transform)),
+    );
+}
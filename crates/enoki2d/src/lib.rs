@@ -18,7 +18,9 @@ use bevy_ecs::{
     component::Component,
     schedule::{common_conditions::on_message, IntoScheduleConfigs},
 };
+use bevy_image::{Image, TextureAtlasPlugin};
 use bevy_math::Vec2;
+use bevy_mesh::Mesh;
 use bevy_reflect::{Reflect, TypePath};
 use bevy_render::sync_world::SyncToRenderWorld;
 use bevy_shader::Shader;
@@ -27,26 +29,52 @@ use color::ColorParticle2dMaterial;
 use serde::{Deserialize, Serialize};
 use values::Rval;
 
+mod budget;
+mod collision;
 mod color;
 mod curve;
+mod emission;
+mod force;
 mod loader;
+mod mask;
 mod material;
+mod mesh;
+mod noise;
+mod polygon;
 mod sprite;
+mod trail;
 mod update;
 mod values;
 
 #[allow(unused)]
 pub mod prelude {
+    pub use super::budget::ParticleBudget;
+    pub use super::collision::{
+        ParticleCollider, ParticleColliders, ParticleCollisionQuery, ParticleHit,
+    };
     pub use super::color::ColorParticle2dMaterial;
     pub use super::curve::{LerpThat, MultiCurve /* , ParticleEaseFunction */};
+    pub use super::force::{
+        ForceFalloff, ForceFieldKind, ParticleForceField, ParticleForceFields, ParticleForceLayers,
+    };
     pub use super::loader::ParticleEffectLoader;
-    pub use super::material::{Particle2dMaterial, Particle2dMaterialPlugin};
-    pub use super::sprite::SpriteParticle2dMaterial;
-    pub use super::update::{OneShot, ParticleEffectInstance, ParticleSpawnerState, ParticleStore};
-    pub use super::values::{Random, Rval};
+    pub use super::mask::ParticleImageMasks;
+    pub use super::material::{
+        Particle2dMaterial, Particle2dMaterialPlugin, Particle2dPipelineKey, ParticleBlendMode,
+    };
+    pub use super::mesh::ParticleMeshShapes;
+    pub use super::polygon::ParticlePolygons;
+    pub use super::sprite::{SpriteParticle2dMaterial, MAX_ATLAS_RECTS};
+    pub use super::update::{
+        CullingPolicy, OneShot, OverflowPolicy, ParticleEffectInstance, ParticleSpawnerState,
+        ParticleStore,
+    };
+    pub use super::values::{ParticleRng, Random, Rval};
     pub use super::{
-        Attractor, EmissionShape, EnokiPlugin, NoAutoAabb, Particle2dEffect, ParticleEffectHandle,
-        ParticleSpawner,
+        Attractor, Burst, Collision, CustomChannel, EmissionMode, EmissionShape, EnokiPlugin,
+        ImageMask, MaskChannel, MeshShape, NoAutoAabb, Particle2dEffect, ParticleEffectHandle,
+        ParticleOrientation, ParticleSpawner, SimulationSpace, SpriteAnimation,
+        SpriteAnimationMode, SubEmitter, SubEmitterTrigger, Trail, Turbulence, TurbulenceKind,
     };
 }
 
@@ -58,6 +86,8 @@ pub(crate) const PARTICLE_COLOR_FRAG: Handle<Shader> =
     uuid_handle!("f60a0cf3-19d3-4425-b6f8-b06bf7ba2f34");
 pub(crate) const PARTICLE_SPRITE_FRAG: Handle<Shader> =
     uuid_handle!("9b13ccf9-eea1-4515-bdd1-1b4131368f71");
+pub(crate) const PARTICLE_TRAIL_VERTEX: Handle<Shader> =
+    uuid_handle!("3f0b6a8e-5d21-4c7e-9a3b-6e2f1d8c4b57");
 
 pub struct EnokiPlugin;
 impl Plugin for EnokiPlugin {
@@ -90,6 +120,17 @@ impl Plugin for EnokiPlugin {
             Shader::from_wgsl
         );
 
+        load_internal_asset!(
+            app,
+            PARTICLE_TRAIL_VERTEX,
+            "shaders/particle_trail_vertex.wgsl",
+            Shader::from_wgsl
+        );
+
+        if !app.is_plugin_added::<TextureAtlasPlugin>() {
+            app.add_plugins(TextureAtlasPlugin);
+        }
+
         app.add_plugins(material::Particle2dMaterialPlugin::<SpriteParticle2dMaterial>::default());
         app.add_plugins(material::Particle2dMaterialPlugin::<ColorParticle2dMaterial>::default());
 
@@ -98,6 +139,16 @@ impl Plugin for EnokiPlugin {
         app.register_type::<update::ParticleSpawnerState>();
         app.register_type::<update::Particle>();
         app.register_type::<ParticleEffectHandle>();
+        app.register_type::<collision::ParticleCollider>();
+        app.init_resource::<collision::ParticleColliders>();
+        app.register_type::<force::ParticleForceField>();
+        app.register_type::<force::ParticleForceLayers>();
+        app.init_resource::<force::ParticleForceFields>();
+        app.init_resource::<mask::ParticleImageMasks>();
+        app.init_resource::<mesh::ParticleMeshShapes>();
+        app.init_resource::<polygon::ParticlePolygons>();
+        app.register_type::<budget::ParticleBudget>();
+        app.init_resource::<budget::ParticleBudget>();
         app.init_asset::<Particle2dEffect>();
         app.init_asset_loader::<loader::ParticleEffectLoader>();
 
@@ -128,7 +179,14 @@ impl Plugin for EnokiPlugin {
                 loader::reload_effect,
                 update::clone_effect,
                 update::remove_finished_spawner,
+                collision::collect_colliders.before(update::update_spawner),
+                force::collect_force_fields.before(update::update_spawner),
+                mask::collect_image_masks.before(update::update_spawner),
+                mesh::collect_mesh_shapes.before(update::update_spawner),
+                polygon::collect_polygons.before(update::update_spawner),
+                budget::update_particle_budget.before(update::update_spawner),
                 update::update_spawner,
+                sprite::sync_atlas_rects,
             ),
         );
 
@@ -186,7 +244,70 @@ impl Default for ParticleSpawner<ColorParticle2dMaterial> {
 pub enum EmissionShape {
     #[default]
     Point,
+    /// filled circle with a radius
     Circle(f32),
+    /// rectangle with a size, centered on the spawner
+    Rect(Vec2),
+    /// circle with a hole
+    Ring { inner: f32, outer: f32 },
+    /// circle segment, angles in radians counter clockwise
+    /// from the spawner right direction
+    Arc { radius: f32, start: f32, end: f32 },
+    /// line between two points, the normal points to the left
+    Line(Vec2, Vec2),
+    /// closed polygon through its corners, may be concave
+    Polygon(Vec<Vec2>),
+    /// the pixels of an image, above a threshold
+    Image(ImageMask),
+    /// the triangles of a mesh, weighted by area
+    Mesh(MeshShape),
+}
+
+/// Emits from the pixels of an image. The image needs to
+/// keep its data in the main world, see `RenderAssetUsages`.
+#[derive(Deserialize, Serialize, Reflect, Clone, Debug, PartialEq)]
+pub struct ImageMask {
+    /// asset path of the image. Loaded by the `ParticleEffectLoader`.
+    pub path: Option<String>,
+    /// the image. Set this directly, when not using a path.
+    #[serde(skip)]
+    pub handle: Option<Handle<Image>>,
+    pub channel: MaskChannel,
+    /// pixels with a channel value above it emit, from 0 to 1
+    pub threshold: f32,
+    /// world size of a pixel
+    pub pixel_size: f32,
+    /// multiplies the particle color with the pixel color
+    pub use_color: bool,
+}
+
+/// Emits from the triangles of a `TriangleList` mesh, like the
+/// mesh of a `Mesh2d`. The edge mode emits from its outline.
+#[derive(Deserialize, Serialize, Reflect, Clone, Debug, PartialEq)]
+pub struct MeshShape {
+    /// asset path of the mesh. Loaded by the `ParticleEffectLoader`.
+    pub path: Option<String>,
+    /// the mesh. Set this directly, when not using a path.
+    #[serde(skip)]
+    pub handle: Option<Handle<Mesh>>,
+    /// scales the mesh positions
+    pub scale: Vec2,
+}
+
+#[derive(Deserialize, Serialize, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MaskChannel {
+    #[default]
+    Alpha,
+    Luminance,
+}
+
+#[derive(Deserialize, Serialize, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+pub enum EmissionMode {
+    /// emits from the whole area of the shape
+    #[default]
+    Volume,
+    /// emits from the outline only
+    Edge,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, Reflect)]
@@ -196,6 +317,151 @@ pub struct Attractor {
     pub min_distance: f32,
 }
 
+/// The noise a `Turbulence` field is made of
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Reflect, PartialEq)]
+pub enum TurbulenceKind {
+    /// smooth random push in every direction
+    Value,
+    /// swirling, divergence free flow. Great for smoke and fire
+    Curl,
+}
+
+/// Noise force field, pushing particles around by their world position.
+#[derive(Deserialize, Serialize, Clone, Debug, Reflect)]
+pub struct Turbulence {
+    pub kind: TurbulenceKind,
+    /// scale of the noise, smaller values make larger swirls
+    pub frequency: f32,
+    pub strength: f32,
+    /// layers of finer noise, added on top
+    pub octaves: u32,
+    /// moves the noise over time, in world units per second
+    pub scroll: Vec2,
+}
+
+/// A ribbon, following each particle through its recent positions.
+/// Rendered with the material of the spawner, the `uv.x` runs from
+/// the particle (0) to the end of the trail (1).
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Trail {
+    /// positions kept per particle
+    pub length: u32,
+    /// seconds between two recorded positions
+    pub interval: f32,
+    pub width: f32,
+    /// multiplies the width along the trail
+    pub width_curve: Option<curve::MultiCurve<f32>>,
+    /// multiplies the particle color along the trail
+    pub color_curve: Option<curve::MultiCurve<LinearRgba>>,
+}
+
+/// Plays a range of sprite sheet frames, instead of the whole
+/// sheet over the lifetime. Frames are numbered like the frames
+/// of the `SpriteParticle2dMaterial`, grid cells or atlas rects.
+#[derive(Deserialize, Serialize, Clone, Debug, Reflect)]
+pub struct SpriteAnimation {
+    pub first_frame: u32,
+    pub frame_count: u32,
+    pub mode: SpriteAnimationMode,
+    /// starts each particle on a random frame of the range
+    pub random_start: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, Reflect, PartialEq)]
+pub enum SpriteAnimationMode {
+    /// plays the range once over the lifetime
+    #[default]
+    OverLifetime,
+    /// loops the range with a fixed frames per second
+    Loop(f32),
+    /// plays the range back and forth with a fixed frames per second
+    PingPong(f32),
+    /// keeps the start frame, combine with `random_start`
+    /// to pick one variant per particle
+    Static,
+}
+
+/// Source of a custom per particle value, passed to the shaders
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub enum CustomChannel {
+    /// random value per particle, between min and max
+    Random(f32, f32),
+    /// follows a curve over the lifetime
+    Curve(curve::MultiCurve<f32>),
+    /// starts with a value, gameplay systems can overwrite it
+    /// with `Particle::set_channel`
+    Value(f32),
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, Reflect, PartialEq)]
+pub enum SimulationSpace {
+    /// particles move independent of the spawner
+    #[default]
+    World,
+    /// particles follow the translation, rotation and scale of the
+    /// spawner. The particle size is not scaled.
+    Local,
+    /// follows the spawner partially, 0 is world and 1 local space
+    Hybrid(f32),
+}
+
+/// How particles are rotated
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, Reflect, PartialEq)]
+pub enum ParticleOrientation {
+    /// keeps the spawner rotation, turned by the angular speed
+    #[default]
+    Fixed,
+    /// the local up axis follows the movement, ignores the angular speed
+    AlignToVelocity,
+    /// the local up axis faces the emission direction, turned by the angular speed
+    AlignToEmitterDirection,
+}
+
+/// Emits `count` particles, `time` seconds into the emitter cycle.
+/// Repeats `cycles` times, every `interval` seconds.
+#[derive(Deserialize, Serialize, Clone, Debug, Reflect)]
+pub struct Burst {
+    pub time: f32,
+    pub count: u32,
+    pub cycles: u32,
+    pub interval: f32,
+}
+
+/// Collision response of an effect, against `ParticleCollider`
+/// entities and custom `ParticleCollisionQuery`s.
+#[derive(Deserialize, Serialize, Clone, Debug, Reflect)]
+pub struct Collision {
+    /// velocity kept along the surface normal, after a hit
+    pub bounce: f32,
+    /// velocity lost along the surface, 0 - 1
+    pub friction: f32,
+    /// kills the particle on its first hit
+    pub kill_on_hit: bool,
+}
+
+/// When a sub emitter fires, during the life of a particle
+#[derive(Deserialize, Serialize, Clone, Debug, Reflect, PartialEq)]
+pub enum SubEmitterTrigger {
+    Birth,
+    Death,
+    /// every n seconds, while the particle is alive
+    Interval(f32),
+}
+
+/// Spawns a child effect at the position of a particle.
+/// The child is a `OneShot::Despawn` spawner, using the material of its parent,
+/// seeded by the parent rng. Sub emitters nest at most four generations deep,
+/// each spawner creates at most `ParticleSpawnerState::max_sub_emits` per frame.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SubEmitter {
+    pub trigger: SubEmitterTrigger,
+    /// asset path of the child effect. Loaded by the `ParticleEffectLoader`.
+    pub path: Option<String>,
+    /// the child effect. Set this directly, when not using a path.
+    #[serde(skip)]
+    pub handle: Option<Handle<Particle2dEffect>>,
+}
+
 /// holds the effect asset. Changing the Asset, will
 /// effect all spanwers using it. Instead use `ParticleEffectInstance`,
 /// which is a unique copy for each spawner,
@@ -214,7 +480,15 @@ impl From<Handle<Particle2dEffect>> for ParticleEffectHandle {
 pub struct Particle2dEffect {
     pub spawn_rate: f32,
     pub spawn_amount: u32,
+    /// emits `spawn_amount` particles every `spawn_distance` world units
+    /// the spawner travels. Replaces the time based `spawn_rate`.
+    pub spawn_distance: Option<f32>,
     pub emission_shape: EmissionShape,
+    /// emit from the area or the outline of the shape
+    pub emission_mode: Option<EmissionMode>,
+    /// the `direction` becomes relative to the shape normal,
+    /// `(1, 0)` points straight out
+    pub emit_along_normal: Option<bool>,
     pub lifetime: Rval<f32>,
     pub linear_speed: Option<Rval<f32>>,
     pub linear_acceleration: Option<Rval<f32>>,
@@ -228,9 +502,59 @@ pub struct Particle2dEffect {
     pub linear_damp: Option<Rval<f32>>,
     pub angular_damp: Option<Rval<f32>>,
     pub scale_curve: Option<curve::MultiCurve<f32>>,
+    /// scale per axis, replaces `scale`
+    pub axis_scale: Option<(Rval<f32>, Rval<f32>)>,
+    /// scale per axis over the lifetime, replaces `scale_curve`
+    pub axis_scale_curve: Option<curve::MultiCurve<Vec2>>,
     pub color_curve: Option<curve::MultiCurve<LinearRgba>>,
+    /// multiplies the linear speed over the lifetime
+    pub speed_curve: Option<curve::MultiCurve<f32>>,
+    /// multiplies the angular speed over the lifetime
+    pub angular_speed_curve: Option<curve::MultiCurve<f32>>,
+    /// multiplies the gravity over the lifetime
+    pub gravity_curve: Option<curve::MultiCurve<f32>>,
+    /// multiplies the alpha over the lifetime, on top of `color` or `color_curve`
+    pub alpha_curve: Option<curve::MultiCurve<f32>>,
     pub attractors: Option<Vec<Attractor>>,
+    /// moves living particles along with the spawner. Same as
+    /// `SimulationSpace::Local`, ignored when `simulation_space` is set
     pub relative_positioning: Option<bool>,
+    /// the space particles are simulated in
+    pub simulation_space: Option<SimulationSpace>,
+    /// adds this fraction of the spawner velocity to new particles
+    pub inherit_velocity: Option<f32>,
+    /// how particles are rotated. Defaults to `Fixed`
+    pub orientation: Option<ParticleOrientation>,
+    /// stretches particles along their local up axis, by their speed.
+    /// Best combined with `AlignToVelocity`
+    pub velocity_stretch: Option<f32>,
+    /// seconds to wait, before the emitter starts emitting
+    pub start_delay: Option<f32>,
+    /// seconds the emitter emits particles. Emits forever if `None`
+    pub emitter_duration: Option<f32>,
+    /// restarts the emitter after `emitter_duration`. Defaults to `true`
+    pub looping: Option<bool>,
+    /// seconds simulated on the first frame, so the effect
+    /// is already running when it shows up
+    pub prewarm: Option<f32>,
+    /// timed bursts, on top of the continuous emission
+    pub bursts: Option<Vec<Burst>>,
+    /// multiplies the spawn rate over the `emitter_duration`.
+    /// Requires an `emitter_duration`, ignored on emitters running forever
+    pub spawn_rate_curve: Option<curve::MultiCurve<f32>>,
+    /// child effects, spawned on birth, death or during the life of a particle
+    pub sub_emitters: Option<Vec<SubEmitter>>,
+    /// collides particles with `ParticleCollider` entities
+    pub collision: Option<Collision>,
+    /// noise force fields
+    pub turbulence: Option<Vec<Turbulence>>,
+    /// renders a trail behind each particle
+    pub trail: Option<Trail>,
+    /// up to four custom values per particle, available
+    /// in the shaders as `VertexOutput::channels`
+    pub custom_channels: Option<Vec<CustomChannel>>,
+    /// frame range and playback of a sprite sheet material
+    pub sprite_animation: Option<SpriteAnimation>,
 }
 
 impl Default for Particle2dEffect {
@@ -238,7 +562,10 @@ impl Default for Particle2dEffect {
         Self {
             spawn_rate: 0.1,
             spawn_amount: 1,
+            spawn_distance: None,
             emission_shape: EmissionShape::Point,
+            emission_mode: None,
+            emit_along_normal: None,
             lifetime: Rval::new(1., 0.0),
             linear_speed: Some(Rval(100., 0.1)),
             linear_acceleration: None,
@@ -252,9 +579,31 @@ impl Default for Particle2dEffect {
             linear_damp: None,
             angular_damp: None,
             scale_curve: None,
+            axis_scale: None,
+            axis_scale_curve: None,
             color_curve: None,
+            speed_curve: None,
+            angular_speed_curve: None,
+            gravity_curve: None,
+            alpha_curve: None,
             attractors: None,
             relative_positioning: None,
+            simulation_space: None,
+            inherit_velocity: None,
+            orientation: None,
+            velocity_stretch: None,
+            start_delay: None,
+            emitter_duration: None,
+            looping: None,
+            prewarm: None,
+            bursts: None,
+            spawn_rate_curve: None,
+            sub_emitters: None,
+            collision: None,
+            turbulence: None,
+            trail: None,
+            custom_channels: None,
+            sprite_animation: None,
         }
     }
 }
@@ -1,5 +1,5 @@
 use super::ParticleEffectInstance;
-use crate::{Particle2dEffect, ParticleEffectHandle};
+use crate::{CustomChannel, EmissionShape, Particle2dEffect, ParticleEffectHandle};
 use bevy_asset::{io::Reader, AssetEvent, AssetLoadError, AssetLoader, Assets, LoadContext};
 use bevy_ecs::{
     component::Component,
@@ -8,6 +8,7 @@ use bevy_ecs::{
     query::With,
     system::{Commands, Query, Res},
 };
+use bevy_log::warn;
 use bevy_reflect::TypePath;
 
 #[derive(Default, TypePath)]
@@ -21,7 +22,7 @@ impl AssetLoader for ParticleEffectLoader {
         &self,
         reader: &mut dyn Reader,
         _settings: &Self::Settings,
-        _load_context: &mut LoadContext<'_>,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await.unwrap();
@@ -36,6 +37,65 @@ impl AssetLoader for ParticleEffectLoader {
             curve.sort();
         }
 
+        if let Some(curve) = asset.axis_scale_curve.as_mut() {
+            curve.sort();
+        }
+
+        for curve in [
+            &mut asset.spawn_rate_curve,
+            &mut asset.speed_curve,
+            &mut asset.angular_speed_curve,
+            &mut asset.gravity_curve,
+            &mut asset.alpha_curve,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            curve.sort();
+        }
+
+        if let Some(trail) = asset.trail.as_mut() {
+            if let Some(curve) = trail.width_curve.as_mut() {
+                curve.sort();
+            }
+            if let Some(curve) = trail.color_curve.as_mut() {
+                curve.sort();
+            }
+        }
+
+        for channel in asset.custom_channels.iter_mut().flatten() {
+            if let CustomChannel::Curve(curve) = channel {
+                curve.sort();
+            }
+        }
+
+        if asset.spawn_rate_curve.is_some() && asset.emitter_duration.is_none() {
+            warn!(
+                "{}: `spawn_rate_curve` is ignored without an `emitter_duration`",
+                load_context.path()
+            );
+        }
+
+        match &mut asset.emission_shape {
+            EmissionShape::Image(mask) => {
+                if let Some(path) = &mask.path {
+                    mask.handle = Some(load_context.load(path));
+                }
+            }
+            EmissionShape::Mesh(shape) => {
+                if let Some(path) = &shape.path {
+                    shape.handle = Some(load_context.load(path));
+                }
+            }
+            _ => (),
+        }
+
+        for sub_emitter in asset.sub_emitters.iter_mut().flatten() {
+            if let Some(path) = &sub_emitter.path {
+                sub_emitter.handle = Some(load_context.load(path));
+            }
+        }
+
         Ok(asset)
     }
 
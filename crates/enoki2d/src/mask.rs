@@ -0,0 +1,262 @@
+use crate::{
+    update::ParticleEffectInstance, values::ParticleRng, EmissionShape, ImageMask, MaskChannel,
+};
+use bevy_asset::{AssetEvent, AssetId, Assets};
+use bevy_color::{LinearRgba, Luminance};
+use bevy_ecs::{
+    message::MessageReader,
+    resource::Resource,
+    system::{Query, Res, ResMut},
+};
+use bevy_image::Image;
+use bevy_math::{IVec2, Vec2};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// tries to hit an outline pixel, before walking to the outline
+const EDGE_TRIES: usize = 16;
+
+/// an emitting pixel, centered on the image with y up
+#[derive(Clone, Copy)]
+pub(crate) struct MaskPixel {
+    pub position: Vec2,
+    /// outward normal on the outline, zero inside or where it cancels out
+    pub normal: Vec2,
+    pub color: LinearRgba,
+}
+
+/// the pixels of one channel, sorted by their value
+struct ChannelValues {
+    values: Vec<f32>,
+    sorted: Vec<usize>,
+    sorted_values: Vec<f32>,
+}
+
+impl ChannelValues {
+    fn new(values: Vec<f32>) -> Self {
+        let mut sorted = (0..values.len()).collect::<Vec<_>>();
+        sorted.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+        let sorted_values = sorted.iter().map(|index| values[*index]).collect();
+
+        Self {
+            values,
+            sorted,
+            sorted_values,
+        }
+    }
+
+    /// the pixels above the threshold, a tail of `sorted`
+    fn above(&self, threshold: f32) -> &[usize] {
+        &self.sorted[self
+            .sorted_values
+            .partition_point(|value| *value <= threshold)..]
+    }
+}
+
+/// The colors of an image, with every pixel sorted by alpha and
+/// luminance. Any threshold is applied while sampling.
+pub(crate) struct MaskData {
+    width: i32,
+    height: i32,
+    colors: Vec<LinearRgba>,
+    alpha: ChannelValues,
+    luminance: ChannelValues,
+}
+
+/// The pixels of every image used as emission mask. Built once
+/// per image, rebuilt when the image changes.
+#[derive(Resource, Default)]
+pub struct ParticleImageMasks {
+    masks: HashMap<AssetId<Image>, MaskData>,
+}
+
+impl ParticleImageMasks {
+    pub(crate) fn get(&self, mask: &ImageMask) -> Option<&MaskData> {
+        self.masks.get(&mask.handle.as_ref()?.id())
+    }
+}
+
+impl MaskData {
+    fn from_image(image: &Image) -> Self {
+        let (width, height) = (image.width() as i32, image.height() as i32);
+        let colors = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                image
+                    .get_color_at(x as u32, y as u32)
+                    .map(LinearRgba::from)
+                    .unwrap_or(LinearRgba::NONE)
+            })
+            .collect::<Vec<_>>();
+        Self::from_colors(width, height, colors)
+    }
+
+    /// `colors` row by row, from the top left
+    fn from_colors(width: i32, height: i32, colors: Vec<LinearRgba>) -> Self {
+        Self {
+            width,
+            height,
+            alpha: ChannelValues::new(colors.iter().map(|color| color.alpha).collect()),
+            luminance: ChannelValues::new(colors.iter().map(|color| color.luminance()).collect()),
+            colors,
+        }
+    }
+
+    fn channel(&self, channel: MaskChannel) -> &ChannelValues {
+        match channel {
+            MaskChannel::Alpha => &self.alpha,
+            MaskChannel::Luminance => &self.luminance,
+        }
+    }
+
+    /// a random pixel above the threshold, or on its outline
+    pub(crate) fn sample(
+        &self,
+        mask: &ImageMask,
+        edge: bool,
+        rng: &mut ParticleRng,
+    ) -> Option<MaskPixel> {
+        let channel = self.channel(mask.channel);
+        let above = channel.above(mask.threshold);
+        let emits = |x: i32, y: i32| {
+            x >= 0
+                && y >= 0
+                && x < self.width
+                && y < self.height
+                && channel.values[(y * self.width + x) as usize] > mask.threshold
+        };
+
+        let neighbours = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+        let outline = |x: i32, y: i32| {
+            neighbours
+                .iter()
+                .any(|offset| !emits(x + offset.x, y + offset.y))
+        };
+
+        let mut index = *above.get(rng.random_range(0..above.len().max(1)))?;
+        let (mut x, mut y) = (index as i32 % self.width, index as i32 / self.width);
+
+        if edge {
+            let mut tries = 0;
+            while !outline(x, y) && tries < EDGE_TRIES {
+                index = above[rng.random_range(0..above.len())];
+                (x, y) = (index as i32 % self.width, index as i32 / self.width);
+                tries += 1;
+            }
+
+            // inside a large shape, walk in a random direction to the outline
+            let step = neighbours[rng.random_range(0..4)];
+            while !outline(x, y) && emits(x + step.x, y + step.y) {
+                (x, y) = (x + step.x, y + step.y);
+            }
+            index = (y * self.width + x) as usize;
+        }
+
+        // points towards the empty neighbours, image y is down. Zero for
+        // pixels empty on opposite sides, like a line or a lone pixel
+        let normal = neighbours
+            .iter()
+            .filter(|offset| !emits(x + offset.x, y + offset.y))
+            .map(|offset| Vec2::new(offset.x as f32, -offset.y as f32))
+            .sum::<Vec2>();
+
+        let center = Vec2::new(self.width as f32, self.height as f32) * 0.5;
+        Some(MaskPixel {
+            position: Vec2::new(x as f32 - center.x, center.y - y as f32),
+            normal: normal.normalize_or_zero(),
+            color: self.colors[index],
+        })
+    }
+}
+
+pub(crate) fn collect_image_masks(
+    mut masks: ResMut<ParticleImageMasks>,
+    mut events: MessageReader<AssetEvent<Image>>,
+    images: Res<Assets<Image>>,
+    effects: Query<&ParticleEffectInstance>,
+) {
+    for event in events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            masks.masks.remove(id);
+        }
+    }
+
+    for effect in effects.iter().filter_map(|instance| instance.0.as_ref()) {
+        let EmissionShape::Image(mask) = &effect.emission_shape else {
+            continue;
+        };
+        let Some(handle) = &mask.handle else {
+            continue;
+        };
+
+        if masks.masks.contains_key(&handle.id()) {
+            continue;
+        }
+
+        let Some(image) = images.get(handle) else {
+            continue;
+        };
+        masks.masks.insert(handle.id(), MaskData::from_image(image));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_all(data: &MaskData, edge: bool) -> Vec<MaskPixel> {
+        let mask = ImageMask {
+            path: None,
+            handle: None,
+            channel: MaskChannel::Alpha,
+            threshold: 0.5,
+            pixel_size: 1.,
+            use_color: false,
+        };
+        let mut rng = ParticleRng::seeded(7);
+        (0..256)
+            .filter_map(|_| data.sample(&mask, edge, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn lone_pixels_and_lines_are_outline() {
+        // a lone pixel in the corner and a 1px line along the bottom border
+        let (width, height) = (4, 4);
+        let emitting = [(0, 0), (0, 3), (1, 3), (2, 3), (3, 3)];
+        let mut colors = vec![LinearRgba::NONE; (width * height) as usize];
+        for (x, y) in emitting {
+            colors[(y * width + x) as usize] = LinearRgba::WHITE;
+        }
+        let data = MaskData::from_colors(width, height, colors);
+
+        let pixels = sample_all(&data, true);
+        assert_eq!(pixels.len(), 256);
+        for pixel in pixels {
+            let x = (pixel.position.x + 2.) as i32;
+            let y = (2. - pixel.position.y) as i32;
+            assert!(emitting.contains(&(x, y)), "{x} {y} does not emit");
+        }
+    }
+
+    #[test]
+    fn walks_to_the_outline() {
+        // a full image, only the border is outline
+        let (width, height) = (8, 8);
+        let data = MaskData::from_colors(
+            width,
+            height,
+            vec![LinearRgba::WHITE; (width * height) as usize],
+        );
+
+        for pixel in sample_all(&data, true) {
+            let x = (pixel.position.x + 4.) as i32;
+            let y = (4. - pixel.position.y) as i32;
+            assert!(
+                [0, 7].contains(&x) || [0, 7].contains(&y),
+                "{x} {y} is inside"
+            );
+            assert_ne!(pixel.normal, Vec2::ZERO);
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use crate::RenderParticleTag;
 
-use super::{update::Particle, ParticleSpawner, ParticleStore};
-use bevy_app::{App, Plugin};
+use super::{
+    trail::{self, TrailVertex},
+    update::{self, Particle, ParticleEffectInstance},
+    ParticleSpawner, ParticleStore,
+};
+use bevy_app::{App, Plugin, Update};
 use bevy_asset::{Asset, AssetApp, AssetEvent, AssetId, AssetServer, Assets, Handle};
 use bevy_camera::visibility::ViewVisibility;
 use bevy_color::ColorToComponents;
@@ -31,12 +35,13 @@ use bevy_render::{
     },
     render_resource::{
         binding_types::uniform_buffer, AsBindGroup, AsBindGroupError, BindGroup, BindGroupEntries,
-        BindGroupLayoutDescriptor, BindGroupLayoutEntries, BlendState, BufferUsages, BufferVec,
-        ColorTargetState, ColorWrites, CompareFunction, DepthBiasState, DepthStencilState,
-        FrontFace, IndexFormat, OwnedBindingResource, PipelineCache, PolygonMode, PrimitiveState,
-        RenderPipelineDescriptor, ShaderStages, ShaderType, SpecializedRenderPipeline,
-        SpecializedRenderPipelines, StencilFaceState, StencilState, TextureFormat, VertexAttribute,
-        VertexFormat, VertexStepMode,
+        BindGroupLayoutDescriptor, BindGroupLayoutEntries, BlendComponent, BlendFactor,
+        BlendOperation, BlendState, BufferUsages, BufferVec, ColorTargetState, ColorWrites,
+        CompareFunction, DepthBiasState, DepthStencilState, FrontFace, IndexFormat,
+        OwnedBindingResource, PipelineCache, PolygonMode, PrimitiveState, RenderPipelineDescriptor,
+        ShaderStages, ShaderType, SpecializedRenderPipeline, SpecializedRenderPipelines,
+        StencilFaceState, StencilState, TextureFormat, VertexAttribute, VertexFormat,
+        VertexStepMode,
     },
     renderer::{RenderDevice, RenderQueue},
     sync_world::RenderEntity,
@@ -46,7 +51,7 @@ use bevy_render::{
     },
     Extract, ExtractSchedule, Render, RenderApp, RenderSystems,
 };
-use bevy_shader::{Shader, ShaderRef};
+use bevy_shader::{Shader, ShaderDefVal, ShaderRef};
 use bevy_sprite_render::Mesh2dPipelineKey;
 use bevy_tasks::{ComputeTaskPool, ParallelSlice};
 use bevy_transform::components::GlobalTransform;
@@ -58,6 +63,92 @@ pub trait Particle2dMaterial: AsBindGroup + Asset + Clone + Sized {
     fn fragment_shader() -> ShaderRef {
         super::PARTICLE_COLOR_FRAG.into()
     }
+
+    /// vertex shader of the particle quads. Must output the
+    /// `VertexOutput` of `bevy_enoki::particle_vertex_out`.
+    /// Trails always use the built-in trail vertex shader.
+    fn vertex_shader() -> ShaderRef {
+        ShaderRef::Default
+    }
+
+    /// customizes the pipeline, before it is created.
+    /// Add shader defs, change the blending or the depth
+    /// state, based on the `bind_group_data` in the key.
+    #[allow(unused_variables)]
+    #[inline]
+    fn specialize(descriptor: &mut RenderPipelineDescriptor, key: Particle2dPipelineKey<Self>) {}
+
+    /// how the particles are blended with what is behind them
+    fn blend_mode(&self) -> ParticleBlendMode {
+        ParticleBlendMode::Alpha
+    }
+}
+
+/// Blend mode of a particle material. Custom fragment shaders
+/// should pass their result through `blend_color`, from
+/// `bevy_enoki::particle_vertex_out`, for `Multiply` and `Screen`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum ParticleBlendMode {
+    /// regular transparency
+    #[default]
+    Alpha,
+    /// adds the color, for fire and glows
+    Additive,
+    /// for textures, that are already multiplied by their alpha.
+    /// The particle color is premultiplied in the shader, see `vertex_color`
+    Premultiplied,
+    /// darkens what is behind
+    Multiply,
+    /// brightens what is behind, without blowing out
+    Screen,
+}
+
+impl ParticleBlendMode {
+    fn blend_state(self) -> BlendState {
+        let keep_alpha = BlendComponent {
+            src_factor: BlendFactor::Zero,
+            dst_factor: BlendFactor::One,
+            operation: BlendOperation::Add,
+        };
+
+        match self {
+            ParticleBlendMode::Alpha => BlendState::ALPHA_BLENDING,
+            ParticleBlendMode::Premultiplied => BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            ParticleBlendMode::Additive => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+            ParticleBlendMode::Multiply => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::Dst,
+                    dst_factor: BlendFactor::Zero,
+                    operation: BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+            ParticleBlendMode::Screen => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::OneMinusSrc,
+                    operation: BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+        }
+    }
+
+    fn shader_defs(self) -> Vec<ShaderDefVal> {
+        match self {
+            ParticleBlendMode::Premultiplied => vec!["BLEND_PREMULTIPLIED".into()],
+            ParticleBlendMode::Multiply => vec!["BLEND_MULTIPLY".into()],
+            ParticleBlendMode::Screen => vec!["BLEND_SCREEN".into()],
+            _ => vec![],
+        }
+    }
 }
 
 pub struct Particle2dMaterialPlugin<M: Particle2dMaterial> {
@@ -72,13 +163,21 @@ impl<M: Particle2dMaterial> Default for Particle2dMaterialPlugin<M> {
     }
 }
 
-impl<M: Particle2dMaterial> Plugin for Particle2dMaterialPlugin<M> {
+impl<M: Particle2dMaterial> Plugin for Particle2dMaterialPlugin<M>
+where
+    M::Data: PartialEq + Eq + Hash + Clone,
+{
     fn build(&self, app: &mut App) {
         app.init_asset::<M>();
+        app.add_systems(
+            Update,
+            update::spawn_sub_emitters::<M>.after(update::update_spawner),
+        );
 
         app.add_plugins(RenderAssetPlugin::<PreparedParticleMaterial<M>>::default());
         app.sub_app_mut(RenderApp)
             .add_render_command::<Transparent2d, DrawParticle2d<M>>()
+            .add_render_command::<Transparent2d, DrawParticleTrail2d<M>>()
             .init_resource::<SpecializedRenderPipelines<Particle2dPipeline<M>>>()
             .init_resource::<ExtracedParticleSpawner<M>>()
             .init_resource::<ExtractedParticleMaterials<M>>()
@@ -138,6 +237,7 @@ impl<M: Particle2dMaterial> Default for ExtractedParticleMaterials<M> {
 #[derive(Resource, Debug)]
 pub struct ExtracedParticleSpawner<M: Particle2dMaterial> {
     particles: EntityHashMap<Vec<InstanceData>>,
+    trails: EntityHashMap<Vec<TrailVertex>>,
     _m: std::marker::PhantomData<M>,
 }
 
@@ -145,6 +245,7 @@ impl<M: Particle2dMaterial> Default for ExtracedParticleSpawner<M> {
     fn default() -> Self {
         Self {
             particles: Default::default(),
+            trails: Default::default(),
             _m: Default::default(),
         }
     }
@@ -184,12 +285,14 @@ fn extract_particles<M: Particle2dMaterial>(
             &ParticleSpawner<M>,
             &ViewVisibility,
             &RenderEntity,
+            &ParticleEffectInstance,
         )>,
     >,
 ) {
     extraced_batches.particles.clear();
+    extraced_batches.trails.clear();
     query.iter().for_each(|emitter| {
-        let (particle_store, global, material_handle, visbility, render_entity) = emitter;
+        let (particle_store, global, material_handle, visbility, render_entity, effect) = emitter;
         if !visbility.get() || particle_store.is_empty() {
             return;
         }
@@ -203,6 +306,22 @@ fn extract_particles<M: Particle2dMaterial>(
             .into_iter()
             .flatten()
             .collect();
+
+        if let Some(trail) = effect.0.as_ref().and_then(|effect| effect.trail.as_ref()) {
+            let vertices = particle_store
+                .par_splat_map(ComputeTaskPool::get(), None, |_, particles| {
+                    let mut vertices = Vec::new();
+                    particles
+                        .iter()
+                        .for_each(|particle| trail::trail_vertices(particle, trail, &mut vertices));
+                    vertices
+                })
+                .into_iter()
+                .flatten()
+                .collect();
+            extraced_batches.trails.insert(**render_entity, vertices);
+        }
+
         render_material_instances.insert(**render_entity, material_handle.id());
         extraced_batches
             .particles
@@ -225,13 +344,20 @@ fn queue_particles<M: Particle2dMaterial>(
     mut pipelines: ResMut<SpecializedRenderPipelines<Particle2dPipeline<M>>>,
     pipeline_cache: Res<PipelineCache>,
     extract_particles: Res<ExtracedParticleSpawner<M>>,
+    render_materials: Res<RenderParticleMaterials<M>>,
+    prepared_materials: Res<RenderAssets<PreparedParticleMaterial<M>>>,
     z_orders: Query<&ZOrder>,
     views: Query<(&ExtractedView, &RenderVisibleEntities, &Msaa)>,
     mut render_phases: ResMut<ViewSortedRenderPhases<Transparent2d>>,
-) {
+) where
+    M::Data: PartialEq + Eq + Hash + Clone,
+{
     let draw_particles = transparent_2d_draw_functions
         .read()
         .id::<DrawParticle2d<M>>();
+    let draw_trails = transparent_2d_draw_functions
+        .read()
+        .id::<DrawParticleTrail2d<M>>();
 
     for (view, visible_entities, msaa) in &views {
         let Some(transparent_phase) = render_phases.get_mut(&view.retained_view_entity) else {
@@ -241,18 +367,51 @@ fn queue_particles<M: Particle2dMaterial>(
         let mesh_key = Mesh2dPipelineKey::from_msaa_samples(msaa.samples())
             | Mesh2dPipelineKey::from_hdr(view.hdr);
 
-        let key = Particle2dPipelineKey { mesh_key };
-        let pipeline = pipelines.specialize(&pipeline_cache, &custom_pipeline, key);
-
         for (entity, main_entity) in visible_entities.get::<RenderParticleTag>().iter() {
             if extract_particles.particles.get(entity).is_none() {
                 continue;
             }
 
+            let Some(material) = render_materials
+                .get(entity)
+                .and_then(|id| prepared_materials.get(*id))
+            else {
+                continue;
+            };
+
+            let key = Particle2dPipelineKey {
+                mesh_key,
+                trail: false,
+                blend_mode: material.blend_mode,
+                bind_group_data: material.key.clone(),
+            };
+            let pipeline = pipelines.specialize(&pipeline_cache, &custom_pipeline, key.clone());
+
             let Ok(order) = z_orders.get(*entity) else {
                 return;
             };
 
+            if extract_particles
+                .trails
+                .get(entity)
+                .is_some_and(|vertices| !vertices.is_empty())
+            {
+                transparent_phase.add(Transparent2d {
+                    extracted_index: 0,
+                    indexed: false,
+                    extra_index: PhaseItemExtraIndex::None,
+                    sort_key: **order,
+                    entity: (*entity, *main_entity),
+                    pipeline: pipelines.specialize(
+                        &pipeline_cache,
+                        &custom_pipeline,
+                        Particle2dPipelineKey { trail: true, ..key },
+                    ),
+                    draw_function: draw_trails,
+                    batch_range: 0..1,
+                });
+            }
+
             transparent_phase.add(Transparent2d {
                 extracted_index: 0,
                 indexed: false,
@@ -275,12 +434,15 @@ pub struct InstanceData {
     transform: [Vec4; 3],
     color: [f32; 4],
     custom: Vec4,
+    channels: Vec4,
 }
 
 impl From<&Particle> for InstanceData {
     #[inline(always)]
     fn from(value: &Particle) -> Self {
-        let transpose_model_3x3 = value.transform.compute_affine().matrix3.transpose();
+        let mut transform = value.transform;
+        transform.scale.y *= value.stretch;
+        let transpose_model_3x3 = transform.compute_affine().matrix3.transpose();
         Self {
             transform: [
                 transpose_model_3x3
@@ -294,7 +456,13 @@ impl From<&Particle> for InstanceData {
                     .extend(value.transform.translation.z),
             ],
             color: value.color.to_f32_array(),
-            custom: Vec4::new(value.duration_fraction, value.duration, 0., 0.),
+            custom: Vec4::new(
+                value.duration_fraction,
+                value.duration,
+                value.frame.map_or(-1., |frame| frame as f32),
+                0.,
+            ),
+            channels: value.channels,
         }
     }
 }
@@ -305,8 +473,9 @@ impl From<&Particle> for InstanceData {
 #[derive(Resource)]
 pub struct PreparedParticleMaterial<M: Particle2dMaterial> {
     pub bind_group: BindGroup,
+    pub blend_mode: ParticleBlendMode,
     pub _bindings: Vec<(u32, OwnedBindingResource)>,
-    pub _key: Option<M::Data>,
+    pub key: M::Data,
 }
 
 impl<M: Particle2dMaterial> RenderAsset for PreparedParticleMaterial<M> {
@@ -324,6 +493,7 @@ impl<M: Particle2dMaterial> RenderAsset for PreparedParticleMaterial<M> {
         (render_device, pipeline_cache, pipeline, param): &mut SystemParamItem<Self::Param>,
         _: Option<&Self>,
     ) -> Result<Self, bevy_render::render_asset::PrepareAssetError<Self::SourceAsset>> {
+        let key = material.bind_group_data();
         match material.as_bind_group(
             &pipeline.uniform_layout,
             render_device,
@@ -332,8 +502,9 @@ impl<M: Particle2dMaterial> RenderAsset for PreparedParticleMaterial<M> {
         ) {
             Ok(prepared) => Ok(PreparedParticleMaterial {
                 bind_group: prepared.bind_group,
+                blend_mode: material.blend_mode(),
                 _bindings: prepared.bindings.0,
-                _key: None,
+                key,
             }),
             Err(AsBindGroupError::RetryNextUpdate) => {
                 Err(PrepareAssetError::RetryNextUpdate(material))
@@ -394,9 +565,32 @@ fn prepare_particles_instance_buffers<M: Particle2dMaterial>(
         cmd.entity(*entity).insert(batch);
     }
 
+    particle_buffer.trail_buffer.clear();
+    let mut index = 0;
+
+    for (entity, vertices) in extracted_spawner.trails.iter_mut() {
+        if vertices.is_empty() {
+            continue;
+        }
+
+        let batch = ParticleTrailBatch {
+            range: index..index + vertices.len() as u32,
+        };
+
+        index += vertices.len() as u32;
+        vertices.drain(..).for_each(|v| {
+            particle_buffer.trail_buffer.push(v);
+        });
+
+        cmd.entity(*entity).insert(batch);
+    }
+
     particle_buffer
         .instance_buffer
         .write_buffer(&render_device, &render_queue);
+    particle_buffer
+        .trail_buffer
+        .write_buffer(&render_device, &render_queue);
 }
 
 #[derive(Resource)]
@@ -404,6 +598,7 @@ pub struct InstanceBuffer<M: Particle2dMaterial> {
     view_bind_group: Option<BindGroup>,
     instance_buffer: BufferVec<InstanceData>,
     index_buffer: BufferVec<u32>,
+    trail_buffer: BufferVec<TrailVertex>,
     _m: std::marker::PhantomData<M>,
 }
 
@@ -413,6 +608,7 @@ impl<M: Particle2dMaterial> Default for InstanceBuffer<M> {
             view_bind_group: None,
             instance_buffer: BufferVec::<InstanceData>::new(BufferUsages::VERTEX),
             index_buffer: BufferVec::<u32>::new(BufferUsages::INDEX),
+            trail_buffer: BufferVec::<TrailVertex>::new(BufferUsages::VERTEX),
             _m: Default::default(),
         }
     }
@@ -422,21 +618,70 @@ impl<M: Particle2dMaterial> Default for InstanceBuffer<M> {
 pub struct ParticleInstanceBatch {
     pub range: Range<u32>,
 }
+
+#[derive(Component, Debug)]
+pub struct ParticleTrailBatch {
+    pub range: Range<u32>,
+}
 // ----------------------------------------------
 // pipeline
 
 #[derive(Resource)]
 pub struct Particle2dPipeline<M: Particle2dMaterial> {
     vertex_shader: Handle<Shader>,
+    trail_vertex_shader: Handle<Shader>,
     fragment_shader: Handle<Shader>,
     uniform_layout: BindGroupLayoutDescriptor,
     view_layout: BindGroupLayoutDescriptor,
     _m: std::marker::PhantomData<M>,
 }
 
-#[derive(PartialEq, Eq, Hash, Clone)]
-pub struct Particle2dPipelineKey {
-    mesh_key: Mesh2dPipelineKey,
+pub struct Particle2dPipelineKey<M: Particle2dMaterial> {
+    pub mesh_key: Mesh2dPipelineKey,
+    /// renders trails, instead of particle quads
+    pub trail: bool,
+    pub blend_mode: ParticleBlendMode,
+    pub bind_group_data: M::Data,
+}
+
+impl<M: Particle2dMaterial> Eq for Particle2dPipelineKey<M> where M::Data: Eq {}
+
+impl<M: Particle2dMaterial> PartialEq for Particle2dPipelineKey<M>
+where
+    M::Data: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.mesh_key == other.mesh_key
+            && self.trail == other.trail
+            && self.blend_mode == other.blend_mode
+            && self.bind_group_data == other.bind_group_data
+    }
+}
+
+impl<M: Particle2dMaterial> Clone for Particle2dPipelineKey<M>
+where
+    M::Data: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            mesh_key: self.mesh_key,
+            trail: self.trail,
+            blend_mode: self.blend_mode,
+            bind_group_data: self.bind_group_data.clone(),
+        }
+    }
+}
+
+impl<M: Particle2dMaterial> Hash for Particle2dPipelineKey<M>
+where
+    M::Data: Hash,
+{
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.mesh_key.hash(state);
+        self.trail.hash(state);
+        self.blend_mode.hash(state);
+        self.bind_group_data.hash(state);
+    }
 }
 
 impl<M: Particle2dMaterial> FromWorld for Particle2dPipeline<M> {
@@ -449,7 +694,11 @@ impl<M: Particle2dMaterial> FromWorld for Particle2dPipeline<M> {
             ShaderRef::Path(path) => server.load(path),
         };
 
-        let vertex_shader = super::PARTICLE_VERTEX;
+        let vertex_shader = match M::vertex_shader() {
+            ShaderRef::Default => super::PARTICLE_VERTEX,
+            ShaderRef::Handle(handle) => handle,
+            ShaderRef::Path(path) => server.load(path),
+        };
         let render_device = world.resource::<RenderDevice>();
 
         let view_layout = BindGroupLayoutDescriptor::new(
@@ -464,14 +713,18 @@ impl<M: Particle2dMaterial> FromWorld for Particle2dPipeline<M> {
             view_layout,
             uniform_layout: M::bind_group_layout_descriptor(render_device), //world.resource::<ParticleUniformLayout>().0.clone(),
             vertex_shader,
+            trail_vertex_shader: super::PARTICLE_TRAIL_VERTEX,
             fragment_shader,
             _m: std::marker::PhantomData::<M>,
         }
     }
 }
 
-impl<M: Particle2dMaterial> SpecializedRenderPipeline for Particle2dPipeline<M> {
-    type Key = Particle2dPipelineKey;
+impl<M: Particle2dMaterial> SpecializedRenderPipeline for Particle2dPipeline<M>
+where
+    M::Data: PartialEq + Eq + Hash + Clone,
+{
+    type Key = Particle2dPipelineKey<M>;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
         let layout = vec![self.view_layout.clone(), self.uniform_layout.clone()];
@@ -482,56 +735,27 @@ impl<M: Particle2dMaterial> SpecializedRenderPipeline for Particle2dPipeline<M>
             TextureFormat::bevy_default()
         };
 
-        RenderPipelineDescriptor {
+        let (vertex_shader, vertex_buffer) = if key.trail {
+            (self.trail_vertex_shader.clone(), trail_vertex_layout())
+        } else {
+            (self.vertex_shader.clone(), instance_vertex_layout())
+        };
+
+        let mut descriptor = RenderPipelineDescriptor {
             zero_initialize_workgroup_memory: true,
             vertex: bevy_render::render_resource::VertexState {
-                shader: self.vertex_shader.clone(),
+                shader: vertex_shader,
                 shader_defs: vec![],
                 entry_point: Some("vertex".into()),
-                buffers: vec![VertexBufferLayout {
-                    array_stride: 80,
-                    step_mode: VertexStepMode::Instance,
-                    attributes: vec![
-                        // translation
-                        VertexAttribute {
-                            format: VertexFormat::Float32x4,
-                            offset: 0,
-                            shader_location: 0,
-                        },
-                        // rotation
-                        VertexAttribute {
-                            format: VertexFormat::Float32x4,
-                            offset: 16,
-                            shader_location: 1,
-                        },
-                        // scale
-                        VertexAttribute {
-                            format: VertexFormat::Float32x4,
-                            offset: 32,
-                            shader_location: 2,
-                        },
-                        // color
-                        VertexAttribute {
-                            format: VertexFormat::Float32x4,
-                            offset: 48,
-                            shader_location: 3,
-                        },
-                        // custom
-                        VertexAttribute {
-                            format: VertexFormat::Float32x4,
-                            offset: 64,
-                            shader_location: 4,
-                        },
-                    ],
-                }],
+                buffers: vec![vertex_buffer],
             },
             fragment: Some(bevy_render::render_resource::FragmentState {
                 shader: self.fragment_shader.clone(),
-                shader_defs: vec![],
+                shader_defs: key.blend_mode.shader_defs(),
                 entry_point: Some("fragment".into()),
                 targets: vec![Some(ColorTargetState {
                     format,
-                    blend: Some(BlendState::ALPHA_BLENDING),
+                    blend: Some(key.blend_mode.blend_state()),
                     write_mask: ColorWrites::ALL,
                 })],
             }),
@@ -568,7 +792,88 @@ impl<M: Particle2dMaterial> SpecializedRenderPipeline for Particle2dPipeline<M>
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
-        }
+        };
+
+        M::specialize(&mut descriptor, key);
+        descriptor
+    }
+}
+
+fn instance_vertex_layout() -> VertexBufferLayout {
+    VertexBufferLayout {
+        array_stride: 96,
+        step_mode: VertexStepMode::Instance,
+        attributes: vec![
+            // translation
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 0,
+                shader_location: 0,
+            },
+            // rotation
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 16,
+                shader_location: 1,
+            },
+            // scale
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 32,
+                shader_location: 2,
+            },
+            // color
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 48,
+                shader_location: 3,
+            },
+            // custom
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 64,
+                shader_location: 4,
+            },
+            // channels
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 80,
+                shader_location: 5,
+            },
+        ],
+    }
+}
+
+fn trail_vertex_layout() -> VertexBufferLayout {
+    VertexBufferLayout {
+        array_stride: 64,
+        step_mode: VertexStepMode::Vertex,
+        attributes: vec![
+            // position
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 0,
+                shader_location: 0,
+            },
+            // color
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 16,
+                shader_location: 1,
+            },
+            // custom
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 32,
+                shader_location: 2,
+            },
+            // channels
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: 48,
+                shader_location: 3,
+            },
+        ],
     }
 }
 
@@ -582,6 +887,13 @@ type DrawParticle2d<M> = (
     DrawParticleInstanced<M>,
 );
 
+type DrawParticleTrail2d<M> = (
+    SetItemPipeline,
+    SetParticleViewBindGroup<0, M>,
+    SetParticle2dBindGroup<1, M>,
+    DrawParticleTrail<M>,
+);
+
 pub struct SetParticleViewBindGroup<const I: usize, M: Particle2dMaterial>(
     std::marker::PhantomData<M>,
 );
@@ -681,3 +993,32 @@ impl<P: PhaseItem, M: Particle2dMaterial> RenderCommand<P> for DrawParticleInsta
         RenderCommandResult::Success
     }
 }
+
+struct DrawParticleTrail<M: Particle2dMaterial>(std::marker::PhantomData<M>);
+impl<P: PhaseItem, M: Particle2dMaterial> RenderCommand<P> for DrawParticleTrail<M> {
+    type Param = SRes<InstanceBuffer<M>>;
+    type ViewQuery = ();
+    type ItemQuery = Read<ParticleTrailBatch>;
+
+    #[inline]
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        trail_batch: Option<&'w ParticleTrailBatch>,
+        meta: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(batch) = trail_batch else {
+            return RenderCommandResult::Failure("No trail buffer prepared");
+        };
+
+        let Some(trail_buffer) = meta.into_inner().trail_buffer.buffer() else {
+            return RenderCommandResult::Failure("Trail buffer was never written to GPU");
+        };
+
+        pass.set_vertex_buffer(0, trail_buffer.slice(..));
+        pass.draw(batch.range.clone(), 0..1);
+
+        RenderCommandResult::Success
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{update::ParticleEffectInstance, EmissionShape, MeshShape};
+use bevy_asset::{AssetEvent, AssetId, Assets};
+use bevy_ecs::{
+    message::MessageReader,
+    resource::Resource,
+    system::{Query, Res, ResMut},
+};
+use bevy_math::Vec2;
+use bevy_mesh::{Mesh, PrimitiveTopology, VertexAttributeValues};
+use std::collections::HashMap;
+
+/// triangles and outline of a mesh, flattened to 2d
+pub(crate) struct MeshData {
+    pub triangles: Vec<[Vec2; 3]>,
+    /// running total of the triangle areas, for weighted sampling
+    pub areas: Vec<f32>,
+    /// edges used by a single triangle, with their outward normal
+    pub edges: Vec<(Vec2, Vec2, Vec2)>,
+    /// running total of the edge lengths
+    pub lengths: Vec<f32>,
+}
+
+/// The triangles of every mesh used as emission shape.
+/// Built once per mesh, rebuilt when the mesh changes.
+#[derive(Resource, Default)]
+pub struct ParticleMeshShapes {
+    meshes: HashMap<AssetId<Mesh>, MeshData>,
+}
+
+impl ParticleMeshShapes {
+    pub(crate) fn get(&self, shape: &MeshShape) -> Option<&MeshData> {
+        self.meshes.get(&shape.handle.as_ref()?.id())
+    }
+}
+
+impl MeshData {
+    fn from_mesh(mesh: &Mesh) -> Self {
+        let mut data = Self {
+            triangles: Vec::new(),
+            areas: Vec::new(),
+            edges: Vec::new(),
+            lengths: Vec::new(),
+        };
+
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return data;
+        }
+
+        let positions = match mesh.try_attribute(Mesh::ATTRIBUTE_POSITION) {
+            Ok(VertexAttributeValues::Float32x3(positions)) => positions
+                .iter()
+                .map(|p| Vec2::new(p[0], p[1]))
+                .collect::<Vec<_>>(),
+            Ok(VertexAttributeValues::Float32x2(positions)) => {
+                positions.iter().map(|p| Vec2::from_array(*p)).collect()
+            }
+            _ => return data,
+        };
+
+        let indices = match mesh.try_indices_option() {
+            Ok(Some(indices)) => indices.iter().collect::<Vec<_>>(),
+            Ok(None) => (0..positions.len()).collect(),
+            Err(_) => return data,
+        };
+
+        // counts how many triangles share an edge, the outline is used once.
+        // Vertices are welded by position, meshes often duplicate them at seams
+        let weld = |i: usize| {
+            let p = positions[i] + 0.;
+            (p.x.to_bits(), p.y.to_bits())
+        };
+        let mut edge_count = HashMap::<_, (u32, usize, usize, usize)>::new();
+        let mut total_area = 0.;
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+            let (Some(pa), Some(pb), Some(pc)) =
+                (positions.get(a), positions.get(b), positions.get(c))
+            else {
+                continue;
+            };
+
+            total_area += (*pb - *pa).perp_dot(*pc - *pa).abs() * 0.5;
+            data.triangles.push([*pa, *pb, *pc]);
+            data.areas.push(total_area);
+
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                let key = (weld(from).min(weld(to)), weld(from).max(weld(to)));
+                edge_count
+                    .entry(key)
+                    .or_insert((0, from, to, data.triangles.len() - 1))
+                    .0 += 1;
+            }
+        }
+
+        let mut total_length = 0.;
+        let mut outline = edge_count
+            .into_iter()
+            .filter(|(_, (count, ..))| *count == 1)
+            .collect::<Vec<_>>();
+        // the hash map has no stable order, keep sampling reproducible
+        outline.sort_unstable_by_key(|(key, _)| *key);
+
+        for (_, (_, from, to, triangle)) in outline {
+            let (a, b) = (positions[from], positions[to]);
+            let center = data.triangles[triangle].iter().sum::<Vec2>() / 3.;
+            let mut normal = (b - a).perp().normalize_or_zero();
+            if normal.dot(a - center) < 0. {
+                normal = -normal;
+            }
+
+            total_length += a.distance(b);
+            data.edges.push((a, b, normal));
+            data.lengths.push(total_length);
+        }
+
+        data
+    }
+}
+
+pub(crate) fn collect_mesh_shapes(
+    mut shapes: ResMut<ParticleMeshShapes>,
+    mut events: MessageReader<AssetEvent<Mesh>>,
+    meshes: Res<Assets<Mesh>>,
+    effects: Query<&ParticleEffectInstance>,
+) {
+    for event in events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            shapes.meshes.remove(id);
+        }
+    }
+
+    for effect in effects.iter().filter_map(|instance| instance.0.as_ref()) {
+        let EmissionShape::Mesh(shape) = &effect.emission_shape else {
+            continue;
+        };
+        let Some(handle) = &shape.handle else {
+            continue;
+        };
+
+        if shapes.meshes.contains_key(&handle.id()) {
+            continue;
+        }
+
+        let Some(mesh) = meshes.get(handle) else {
+            continue;
+        };
+        shapes.meshes.insert(handle.id(), MeshData::from_mesh(mesh));
+    }
+}
@@ -0,0 +1,73 @@
+use bevy_math::{FloatExt, Vec2};
+
+use crate::{Turbulence, TurbulenceKind};
+
+/// offset between the two samples of a value noise force,
+/// and between the octaves of the fbm
+const SAMPLE_OFFSET: Vec2 = Vec2::new(31.416, 47.853);
+/// step of the finite differences, the curl is sampled with
+const CURL_EPSILON: f32 = 0.01;
+
+/// force of a turbulence field at `position`, `time` seconds into the effect.
+pub(crate) fn turbulence_force(turbulence: &Turbulence, position: Vec2, time: f32) -> Vec2 {
+    let p = (position + turbulence.scroll * time) * turbulence.frequency;
+    let octaves = turbulence.octaves.max(1);
+
+    let force = match turbulence.kind {
+        TurbulenceKind::Value => Vec2::new(fbm(p, octaves), fbm(p + SAMPLE_OFFSET, octaves)),
+        TurbulenceKind::Curl => curl(p, octaves),
+    };
+
+    force * turbulence.strength
+}
+
+/// integer lattice hash, in `-1..1`
+fn hash(x: i32, y: i32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343) ^ (y as u32).wrapping_mul(0xd816_3841);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    h as f32 / u32::MAX as f32 * 2. - 1.
+}
+
+/// smooth value noise, in `-1..1`
+fn value_noise(p: Vec2) -> f32 {
+    let cell = p.floor();
+    let f = p - cell;
+    let u = f * f * (3. - 2. * f);
+    let (x, y) = (cell.x as i32, cell.y as i32);
+
+    let bottom = hash(x, y).lerp(hash(x + 1, y), u.x);
+    let top = hash(x, y + 1).lerp(hash(x + 1, y + 1), u.x);
+    bottom.lerp(top, u.y)
+}
+
+/// sum of value noise octaves, each with double the frequency
+/// and half the amplitude of the previous one. In `-1..1`
+fn fbm(p: Vec2, octaves: u32) -> f32 {
+    let mut sum = 0.;
+    let mut amplitude = 1.;
+    let mut norm = 0.;
+    let mut p = p;
+
+    for _ in 0..octaves {
+        sum += value_noise(p) * amplitude;
+        norm += amplitude;
+        amplitude *= 0.5;
+        p = p * 2. + SAMPLE_OFFSET;
+    }
+
+    sum / norm
+}
+
+/// curl of the fbm potential. The flow is divergence free,
+/// particles swirl around instead of bunching up.
+fn curl(p: Vec2, octaves: u32) -> Vec2 {
+    let dx = Vec2::new(CURL_EPSILON, 0.);
+    let dy = Vec2::new(0., CURL_EPSILON);
+
+    let ddx = fbm(p + dx, octaves) - fbm(p - dx, octaves);
+    let ddy = fbm(p + dy, octaves) - fbm(p - dy, octaves);
+
+    Vec2::new(ddy, -ddx) / (2. * CURL_EPSILON)
+}
@@ -0,0 +1,146 @@
+use crate::{update::ParticleEffectInstance, EmissionShape};
+use bevy_ecs::{
+    resource::Resource,
+    system::{Query, ResMut},
+};
+use bevy_math::Vec2;
+use std::collections::{HashMap, HashSet};
+
+/// the points of a polygon, by their bits
+type PolygonKey = Vec<[u32; 2]>;
+
+/// triangles of a polygon, for uniform volume sampling
+pub(crate) struct PolygonData {
+    pub triangles: Vec<[Vec2; 3]>,
+    /// running total of the triangle areas, for weighted sampling
+    pub areas: Vec<f32>,
+}
+
+/// The triangles of every polygon used as emission shape.
+/// Polygons no longer used by any spawner are dropped.
+#[derive(Resource, Default)]
+pub struct ParticlePolygons {
+    polygons: HashMap<PolygonKey, PolygonData>,
+}
+
+impl ParticlePolygons {
+    pub(crate) fn get(&self, points: &[Vec2]) -> Option<&PolygonData> {
+        self.polygons.get(&key(points))
+    }
+}
+
+fn key(points: &[Vec2]) -> PolygonKey {
+    points
+        .iter()
+        .map(|point| [point.x.to_bits(), point.y.to_bits()])
+        .collect()
+}
+
+impl PolygonData {
+    /// ear clipping, works for concave polygons without self intersections
+    fn from_points(points: &[Vec2]) -> Self {
+        let mut data = Self {
+            triangles: Vec::new(),
+            areas: Vec::new(),
+        };
+
+        // clips counter clockwise, a positive area
+        let mut remaining = points.to_vec();
+        let winding = remaining
+            .iter()
+            .zip(remaining.iter().cycle().skip(1))
+            .map(|(a, b)| a.perp_dot(*b))
+            .sum::<f32>();
+        if winding < 0. {
+            remaining.reverse();
+        }
+
+        let mut total_area = 0.;
+        while remaining.len() > 2 {
+            let count = remaining.len();
+            let ear = (0..count).find(|i| {
+                let a = remaining[(i + count - 1) % count];
+                let b = remaining[*i];
+                let c = remaining[(i + 1) % count];
+                (b - a).perp_dot(c - b) > 0.
+                    && !remaining
+                        .iter()
+                        .filter(|p| **p != a && **p != b && **p != c)
+                        .any(|p| in_triangle(*p, a, b, c))
+            });
+
+            // self intersecting, nothing left to clip
+            let Some(ear) = ear else {
+                break;
+            };
+
+            let a = remaining[(ear + count - 1) % count];
+            let c = remaining[(ear + 1) % count];
+            let b = remaining.remove(ear);
+
+            total_area += (b - a).perp_dot(c - a) * 0.5;
+            data.triangles.push([a, b, c]);
+            data.areas.push(total_area);
+        }
+
+        data
+    }
+}
+
+fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(p - a) >= 0. && (c - b).perp_dot(p - b) >= 0. && (a - c).perp_dot(p - c) >= 0.
+}
+
+pub(crate) fn collect_polygons(
+    mut polygons: ResMut<ParticlePolygons>,
+    effects: Query<&ParticleEffectInstance>,
+) {
+    let mut used = HashSet::new();
+    for effect in effects.iter().filter_map(|instance| instance.0.as_ref()) {
+        let EmissionShape::Polygon(points) = &effect.emission_shape else {
+            continue;
+        };
+
+        let key = key(points);
+        if !polygons.polygons.contains_key(&key) {
+            polygons
+                .polygons
+                .insert(key.clone(), PolygonData::from_points(points));
+        }
+        used.insert(key);
+    }
+
+    // edited polygons would pile up otherwise
+    if used.len() != polygons.polygons.len() {
+        polygons.polygons.retain(|key, _| used.contains(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concave_polygon_is_covered() {
+        // an L shape, clockwise
+        let points = [
+            Vec2::new(0., 0.),
+            Vec2::new(0., 2.),
+            Vec2::new(1., 2.),
+            Vec2::new(1., 1.),
+            Vec2::new(2., 1.),
+            Vec2::new(2., 0.),
+        ];
+        let data = PolygonData::from_points(&points);
+
+        assert_eq!(data.triangles.len(), points.len() - 2);
+        assert_eq!(data.areas.last().copied(), Some(3.));
+
+        // no triangle reaches into the notch
+        let notch = Vec2::new(1.5, 1.5);
+        assert!(data
+            .triangles
+            .iter()
+            .all(|[a, b, c]| !in_triangle(notch, *a, *b, *c)));
+    }
+}
@@ -1,8 +1,8 @@
-#import bevy_enoki::particle_vertex_out::{ VertexOutput }
+#import bevy_enoki::particle_vertex_out::{ VertexOutput, blend_color, vertex_color }
 
 @group(1) @binding(0) var<uniform> color: vec4<f32>;
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-	return in.color * color;
+	return blend_color(vertex_color(in.color) * color);
 }
@@ -1,29 +1,49 @@
-#import bevy_enoki::particle_vertex_out::{ VertexOutput }
+#import bevy_enoki::particle_vertex_out::{ VertexOutput, blend_color, vertex_color }
+
+struct AtlasRects {
+    rects: array<vec4<f32>, 64>,
+};
 
 @group(1) @binding(0) var texture: texture_2d<f32>;
 @group(1) @binding(1) var texture_sampler: sampler;
 @group(1) @binding(2) var<uniform> frame_data: vec4<u32>;
+@group(1) @binding(3) var<uniform> atlas: AtlasRects;
 
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-	var out = in.color;
+	var out = vertex_color(in.color);
 
 	let max_hframe = f32(frame_data.x);
 	let max_vframe = f32(frame_data.y);
-
-    let total_frames = max_hframe * max_vframe;
-    let current_frame = floor(in.lifetime_frac * total_frames);
-
-    let hframe = current_frame % max_hframe;
-    let vframe = floor(current_frame / max_hframe);
-
-    let frame_width = 1.0 / max_hframe;
-    let frame_height = 1.0 / max_vframe;
-
-    let u_offset = hframe * frame_width;
-    let v_offset = (max_vframe - vframe - 1.0) * frame_height;
-
-    let uv = in.uv * vec2<f32>(frame_width, frame_height) + vec2<f32>(u_offset, v_offset);
-	return out * textureSample(texture, texture_sampler, uv);
+	let atlas_frames = f32(frame_data.z);
+
+    var total_frames = max_hframe * max_vframe;
+    if atlas_frames > 0.0 {
+        total_frames = atlas_frames;
+    }
+
+    var current_frame = floor(in.lifetime_frac * total_frames);
+    // a negative frame plays the sheet over the lifetime
+    if in.sprite_frame >= 0.0 {
+        current_frame = floor(in.sprite_frame + 0.5) % total_frames;
+    }
+
+    var uv: vec2<f32>;
+    if atlas_frames > 0.0 {
+        let rect = atlas.rects[u32(min(current_frame, atlas_frames - 1.0))];
+        uv = mix(rect.xy, rect.zw, in.uv);
+    } else {
+        let hframe = current_frame % max_hframe;
+        let vframe = floor(current_frame / max_hframe);
+
+        let frame_width = 1.0 / max_hframe;
+        let frame_height = 1.0 / max_vframe;
+
+        let u_offset = hframe * frame_width;
+        let v_offset = (max_vframe - vframe - 1.0) * frame_height;
+
+        uv = in.uv * vec2<f32>(frame_width, frame_height) + vec2<f32>(u_offset, v_offset);
+    }
+	return blend_color(out * textureSample(texture, texture_sampler, uv));
 }
@@ -0,0 +1,27 @@
+#import bevy_render::view::View
+#import bevy_enoki::particle_vertex_out::{ VertexOutput }
+
+@group(0) @binding(0) var<uniform> view: View;
+
+struct VertexIn {
+    @location(0) position: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) custom: vec4<f32>,
+    @location(3) channels: vec4<f32>,
+};
+
+@vertex
+fn vertex(in: VertexIn) -> VertexOutput {
+    var out: VertexOutput;
+
+    out.clip_position = view.clip_from_world * vec4<f32>(in.position.xyz, 1.0);
+    out.color = in.color;
+	out.uv = in.custom.xy;
+
+	out.lifetime_frac = in.custom.z;
+	out.lifetime_total = in.custom.w;
+	out.channels = in.channels;
+	out.sprite_frame = -1.0;
+
+    return out;
+}
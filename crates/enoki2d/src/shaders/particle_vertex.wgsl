@@ -10,6 +10,7 @@ struct VertexIn {
     @location(2) i_scale: vec4<f32>,
     @location(3) i_color: vec4<f32>,
     @location(4) i_lifetime: vec4<f32>,
+    @location(5) i_channels: vec4<f32>,
 };
 
 @vertex
@@ -33,6 +34,8 @@ fn vertex(in: VertexIn) -> VertexOutput {
 
 	out.lifetime_frac = in.i_lifetime.x;
 	out.lifetime_total = in.i_lifetime.y;
+	out.channels = in.i_channels;
+	out.sprite_frame = in.i_lifetime.z;
 
     return out;
 }
@@ -2,8 +2,32 @@
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) @interpolate(flat) color: vec4<f32>,
+    @location(0) color: vec4<f32>,
 	@location(1) uv : vec2<f32>,
 	@location(2) lifetime_frac : f32,
 	@location(3) lifetime_total : f32,
+	@location(4) channels : vec4<f32>,
+	@location(5) sprite_frame : f32,
 };
+
+/// the particle color, ready to multiply with a texture.
+/// Premultiplied blending scales it by its alpha, so fading the alpha fades the color
+fn vertex_color(color: vec4<f32>) -> vec4<f32> {
+#ifdef BLEND_PREMULTIPLIED
+	return vec4<f32>(color.rgb * color.a, color.a);
+#else
+	return color;
+#endif
+}
+
+/// adjusts the fragment color to the blend mode of the material
+fn blend_color(color: vec4<f32>) -> vec4<f32> {
+	var out = color;
+#ifdef BLEND_MULTIPLY
+	out = vec4<f32>(mix(vec3<f32>(1.0), color.rgb, color.a), color.a);
+#endif
+#ifdef BLEND_SCREEN
+	out = vec4<f32>(color.rgb * color.a, color.a);
+#endif
+	return out;
+}
@@ -1,9 +1,31 @@
-use super::{Particle2dMaterial, PARTICLE_SPRITE_FRAG};
-use bevy_asset::{Asset, Handle};
-use bevy_image::Image;
-use bevy_math::UVec4;
+use super::{material::ParticleBlendMode, Particle2dMaterial, PARTICLE_SPRITE_FRAG};
+use bevy_asset::{Asset, AssetEvent, AssetId, Assets, Handle};
+use bevy_ecs::{
+    message::MessageReader,
+    system::{Res, ResMut},
+};
+use bevy_image::{Image, TextureAtlasLayout};
+use bevy_math::{UVec4, Vec2, Vec4};
 use bevy_reflect::TypePath;
-use bevy_render::render_resource::AsBindGroup;
+use bevy_render::render_resource::{AsBindGroup, ShaderType};
+
+/// Maximum rects of an atlas. The rects are a fixed size
+/// uniform array, storage buffers are not available on wasm.
+pub const MAX_ATLAS_RECTS: usize = 64;
+
+/// Normalized `min.xy` and `max.xy` of each atlas rect
+#[derive(ShaderType, Clone, Debug)]
+pub(crate) struct AtlasRects {
+    rects: [Vec4; MAX_ATLAS_RECTS],
+}
+
+impl Default for AtlasRects {
+    fn default() -> Self {
+        Self {
+            rects: [Vec4::ZERO; MAX_ATLAS_RECTS],
+        }
+    }
+}
 
 /// Sprite Material lets you add textures and animations
 /// to particles.
@@ -14,13 +36,21 @@ pub struct SpriteParticle2dMaterial {
     pub texture: Option<Handle<Image>>,
     #[uniform(2)]
     frame_data: UVec4,
+    #[uniform(3)]
+    atlas: AtlasRects,
+    /// frames of the atlas, kept in sync with the layout asset
+    pub atlas_layout: Option<Handle<TextureAtlasLayout>>,
+    pub blend_mode: ParticleBlendMode,
 }
 
 impl Default for SpriteParticle2dMaterial {
     fn default() -> Self {
         Self {
             texture: None,
-            frame_data: UVec4::ONE,
+            frame_data: UVec4::new(1, 1, 0, 0),
+            atlas: AtlasRects::default(),
+            atlas_layout: None,
+            blend_mode: ParticleBlendMode::Alpha,
         }
     }
 }
@@ -30,6 +60,9 @@ impl SpriteParticle2dMaterial {
         Self {
             texture: Some(texture),
             frame_data: UVec4::new(max_hframes, max_vframes, 0, 0),
+            atlas: AtlasRects::default(),
+            atlas_layout: None,
+            blend_mode: ParticleBlendMode::Alpha,
         }
     }
 
@@ -37,7 +70,40 @@ impl SpriteParticle2dMaterial {
         Self {
             texture: Some(texture),
             frame_data: UVec4::new(1, 1, 0, 0),
+            atlas: AtlasRects::default(),
+            atlas_layout: None,
+            blend_mode: ParticleBlendMode::Alpha,
+        }
+    }
+
+    /// uses the rects of a texture atlas as frames, in the order of the layout.
+    /// Only the first `MAX_ATLAS_RECTS` rects are used. The rects are copied,
+    /// once the layout is loaded and again whenever it changes.
+    pub fn from_atlas(texture: Handle<Image>, layout: Handle<TextureAtlasLayout>) -> Self {
+        Self {
+            texture: Some(texture),
+            frame_data: UVec4::new(1, 1, 0, 0),
+            atlas: AtlasRects::default(),
+            atlas_layout: Some(layout),
+            blend_mode: ParticleBlendMode::Alpha,
+        }
+    }
+
+    fn set_atlas_rects(&mut self, layout: &TextureAtlasLayout) {
+        let size = layout.size.as_vec2().max(Vec2::ONE);
+        let count = layout.textures.len().min(MAX_ATLAS_RECTS);
+        self.atlas = AtlasRects::default();
+        for (rect, texture) in self.atlas.rects.iter_mut().zip(layout.textures.iter()) {
+            let min = texture.min.as_vec2() / size;
+            let max = texture.max.as_vec2() / size;
+            *rect = Vec4::new(min.x, min.y, max.x, max.y);
         }
+        self.frame_data.z = count as u32;
+    }
+
+    pub fn with_blend_mode(mut self, blend_mode: ParticleBlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
     }
 }
 
@@ -45,4 +111,52 @@ impl Particle2dMaterial for SpriteParticle2dMaterial {
     fn fragment_shader() -> bevy_shader::ShaderRef {
         PARTICLE_SPRITE_FRAG.into()
     }
+
+    fn blend_mode(&self) -> ParticleBlendMode {
+        self.blend_mode
+    }
+}
+
+/// copies the atlas rects into new materials and
+/// into every material of a changed layout
+pub(crate) fn sync_atlas_rects(
+    mut layout_events: MessageReader<AssetEvent<TextureAtlasLayout>>,
+    mut material_events: MessageReader<AssetEvent<SpriteParticle2dMaterial>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut materials: ResMut<Assets<SpriteParticle2dMaterial>>,
+) {
+    let changed_layouts = layout_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let added_materials = material_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if changed_layouts.is_empty() && added_materials.is_empty() {
+        return;
+    }
+
+    let outdated = materials
+        .iter()
+        .filter_map(|(id, material)| {
+            let layout = material.atlas_layout.as_ref()?.id();
+            (changed_layouts.contains(&layout) || added_materials.contains(&id))
+                .then_some((id, layout))
+        })
+        .collect::<Vec<(AssetId<SpriteParticle2dMaterial>, _)>>();
+
+    for (id, layout) in outdated {
+        let (Some(layout), Some(material)) = (layouts.get(layout), materials.get_mut(id)) else {
+            continue;
+        };
+        material.set_atlas_rects(layout);
+    }
 }
@@ -0,0 +1,92 @@
+use crate::{update::Particle, Trail};
+use bevy_color::{ColorToComponents, LinearRgba};
+use bevy_math::{Vec2, Vec3, Vec4};
+use bevy_reflect::Reflect;
+use bevy_render::render_resource::ShaderType;
+
+#[derive(Clone, Debug, Copy, ShaderType, Reflect)]
+pub struct TrailVertex {
+    position: Vec4,
+    color: [f32; 4],
+    custom: Vec4,
+    channels: Vec4,
+}
+
+/// records the particle position, every `interval` seconds
+pub(crate) fn record_trail(particle: &mut Particle, trail: &Trail, delta: f32) {
+    particle.trail_timer += delta;
+    if particle.trail_timer < trail.interval {
+        return;
+    }
+
+    particle.trail_timer = 0.;
+    particle.trail.push_front(particle.transform.translation);
+    particle.trail.truncate(trail.length as usize);
+}
+
+/// appends the triangles of a particle trail, from the particle to the oldest position
+pub(crate) fn trail_vertices(particle: &Particle, trail: &Trail, vertices: &mut Vec<TrailVertex>) {
+    if particle.trail.is_empty() {
+        return;
+    }
+
+    let points = std::iter::once(&particle.transform.translation)
+        .chain(particle.trail.iter())
+        .copied()
+        .collect::<Vec<_>>();
+    let length = trail.length.max(1) as f32;
+
+    let mut normal = Vec2::ZERO;
+    let edges = points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            let previous = points[i.saturating_sub(1)];
+            let next = points[(i + 1).min(points.len() - 1)];
+            normal = (previous - next)
+                .truncate()
+                .try_normalize()
+                .map_or(normal, Vec2::perp);
+
+            let fraction = i as f32 / length;
+            let width = trail.width
+                * trail
+                    .width_curve
+                    .as_ref()
+                    .map_or(1., |curve| curve.lerp(fraction));
+            let color = trail.color_curve.as_ref().map_or(particle.color, |curve| {
+                LinearRgba::from_vec4(particle.color.to_vec4() * curve.lerp(fraction).to_vec4())
+            });
+
+            let offset = (normal * width * 0.5).extend(0.);
+            (*point + offset, *point - offset, fraction, color)
+        })
+        .collect::<Vec<_>>();
+
+    let vertex = |position: Vec3, fraction: f32, side: f32, color: [f32; 4]| TrailVertex {
+        position: position.extend(1.),
+        color,
+        custom: Vec4::new(
+            fraction,
+            side,
+            particle.duration_fraction,
+            particle.duration,
+        ),
+        channels: particle.channels,
+    };
+
+    for segment in edges.windows(2) {
+        let (a_left, a_right, a_fraction, a_color) = segment[0];
+        let (b_left, b_right, b_fraction, b_color) = segment[1];
+        let (a_color, b_color) = (a_color.to_f32_array(), b_color.to_f32_array());
+
+        vertices.extend([
+            vertex(a_left, a_fraction, 0., a_color),
+            vertex(a_right, a_fraction, 1., a_color),
+            vertex(b_left, b_fraction, 0., b_color),
+            vertex(a_right, a_fraction, 1., a_color),
+            vertex(b_right, b_fraction, 1., b_color),
+            vertex(b_left, b_fraction, 0., b_color),
+        ]);
+    }
+}
@@ -1,22 +1,62 @@
-use super::{prelude::EmissionShape, Particle2dEffect, ParticleEffectHandle};
-use crate::values::Random;
-use bevy_asset::Assets;
-use bevy_camera::primitives::Aabb;
-use bevy_color::LinearRgba;
+use super::{
+    collision::{self, ParticleColliders},
+    curve, emission,
+    force::{ParticleForceFields, ParticleForceLayers},
+    mask::ParticleImageMasks,
+    mesh::ParticleMeshShapes,
+    noise,
+    polygon::ParticlePolygons,
+    prelude::Particle2dMaterial,
+    Burst, CustomChannel, Particle2dEffect, ParticleEffectHandle, ParticleOrientation,
+    ParticleSpawner, SimulationSpace, SpriteAnimation, SpriteAnimationMode, SubEmitterTrigger,
+};
+use crate::{
+    trail,
+    values::{ParticleRng, Random},
+};
+use bevy_asset::{Assets, Handle};
+use bevy_camera::{primitives::Aabb, visibility::ViewVisibility};
+use bevy_color::{ColorToComponents, LinearRgba};
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::{
     component::Component,
     entity::Entity,
     query::{Added, Without},
     reflect::ReflectComponent,
-    system::{Commands, Query, Res},
+    system::{Commands, Query, Res, SystemParam},
 };
-use bevy_math::{Vec2, Vec3};
+use bevy_math::{Affine3A, Quat, Vec2, Vec3, Vec4};
 use bevy_reflect::{prelude::ReflectDefault, Reflect};
 use bevy_tasks::{ComputeTaskPool, ParallelSliceMut};
 use bevy_time::{Time, Timer, TimerMode, Virtual};
 use bevy_transform::components::{GlobalTransform, Transform};
-use std::{ops::AddAssign, time::Duration};
+use rand::Rng;
+use std::{collections::VecDeque, ops::AddAssign, time::Duration};
+
+/// What happens to new particles, once a spawner reaches `max_particles`
+#[derive(Clone, Copy, Debug, Default, Reflect, PartialEq)]
+pub enum OverflowPolicy {
+    /// new particles are not spawned
+    #[default]
+    SkipNew,
+    /// replaces the oldest particles
+    RecycleOldest,
+    /// replaces the particles with the least lifetime left
+    RecycleClosestToDeath,
+}
+
+/// How a spawner is simulated, while none of the cameras see it
+#[derive(Clone, Copy, Debug, Default, Reflect, PartialEq)]
+pub enum CullingPolicy {
+    /// keeps simulating off screen
+    #[default]
+    AlwaysSimulate,
+    /// freezes the spawner and its particles
+    Pause,
+    /// pauses, then fast-forwards the missed time when visible again.
+    /// Catches up on at most the given seconds, spread over several frames.
+    CatchUp(f32),
+}
 
 /// Tag Component, deactivates spawner after the first
 /// spawning of particles
@@ -28,13 +68,73 @@ pub enum OneShot {
     Despawn,
 }
 
+/// world state, shared by all spawners during the simulation
+#[derive(Clone, Copy)]
+pub(crate) struct SimulationContext<'a> {
+    pub colliders: &'a ParticleColliders,
+    pub force_fields: &'a ParticleForceFields,
+    pub image_masks: &'a ParticleImageMasks,
+    pub mesh_shapes: &'a ParticleMeshShapes,
+    pub polygons: &'a ParticlePolygons,
+    /// force layers of the simulated spawner
+    pub force_layers: u32,
+}
+
+/// the resources behind the `SimulationContext`
+#[derive(SystemParam)]
+pub(crate) struct SimulationResources<'w> {
+    colliders: Res<'w, ParticleColliders>,
+    force_fields: Res<'w, ParticleForceFields>,
+    image_masks: Res<'w, ParticleImageMasks>,
+    mesh_shapes: Res<'w, ParticleMeshShapes>,
+    polygons: Res<'w, ParticlePolygons>,
+}
+
+/// generations of sub emitters, before children stop spawning children
+const MAX_SUB_EMITTER_DEPTH: u32 = 4;
+
+/// missed time simulated per frame, while a spawner catches up
+const MAX_CATCH_UP_PER_FRAME: Duration = Duration::from_millis(250);
+
+/// fixed time step used to simulate the prewarm phase and catch up
+const PREWARM_STEP: f32 = 1. / 30.;
+
 /// Spawner states controls the spawner
 #[derive(Component, Clone, Debug, Reflect)]
 pub struct ParticleSpawnerState {
+    /// caps the living particles, emission stops or recycles at the cap
     pub max_particles: u32,
+    pub overflow_policy: OverflowPolicy,
+    /// importance under a `ParticleBudget`. Higher priorities keep
+    /// more of their emission, 0 stops emitting when over budget
+    pub priority: f32,
+    /// share of new particles that are emitted, set by the `ParticleBudget`
+    pub emission_scale: f32,
+    pub culling: CullingPolicy,
+    /// time missed while culled, simulated once visible again
+    pub(crate) paused: Duration,
     pub active: bool,
     pub timer: Timer,
     pub previous_position: Option<Vec3>,
+    pub(crate) previous_transform: Option<Transform>,
+    /// world velocity of the spawner, measured from its movement
+    pub velocity: Vec3,
+    /// time since the emitter started, including the start delay
+    pub elapsed: Duration,
+    /// distance travelled since the last distance based emission
+    pub(crate) travelled: f32,
+    pub(crate) prewarmed: bool,
+    /// caps the sub emitter spawners created per frame, each one is an entity
+    pub max_sub_emits: u32,
+    /// sub emitter effects, waiting to be spawned at a world position
+    #[reflect(ignore)]
+    pub(crate) sub_emits: Vec<(Handle<Particle2dEffect>, Vec3)>,
+    /// generation of sub emitters, 0 for spawners not spawned by an effect
+    pub(crate) depth: u32,
+    /// drives every random value of this spawner.
+    /// Seed it for reproducible simulations.
+    #[reflect(ignore)]
+    pub rng: ParticleRng,
 }
 
 /// A clone of the asset, unique to each spawner
@@ -49,33 +149,107 @@ impl Default for ParticleSpawnerState {
         Self {
             active: true,
             max_particles: u32::MAX,
+            overflow_policy: OverflowPolicy::SkipNew,
+            priority: 1.,
+            emission_scale: 1.,
+            culling: CullingPolicy::AlwaysSimulate,
+            paused: Duration::ZERO,
             timer: Timer::new(Duration::ZERO, TimerMode::Repeating),
             previous_position: None,
+            previous_transform: None,
+            velocity: Vec3::ZERO,
+            elapsed: Duration::ZERO,
+            travelled: 0.,
+            prewarmed: false,
+            max_sub_emits: 16,
+            sub_emits: Vec::new(),
+            depth: 0,
+            rng: ParticleRng::default(),
         }
     }
 }
 
+impl ParticleSpawnerState {
+    /// creates a spawner state with a fixed seed.
+    /// The same seed and the same time steps result in the same `ParticleStore`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: ParticleRng::seeded(seed),
+            ..Default::default()
+        }
+    }
+
+    /// resets the random number generator to a new seed
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = ParticleRng::seeded(seed);
+    }
+
+    /// restarts the emitter lifecycle, including the start delay.
+    /// Use this to replay a finished, non looping emitter.
+    pub fn restart(&mut self) {
+        self.active = true;
+        self.elapsed = Duration::ZERO;
+        self.timer.reset();
+    }
+}
+
 /// Component for storing particle data
-#[derive(Component, Default, Clone, Reflect, Deref, DerefMut)]
+#[derive(Component, Default, Clone, PartialEq, Reflect, Deref, DerefMut)]
 pub struct ParticleStore {
     #[deref]
     pub particles: Vec<Particle>,
 }
 
-#[derive(Clone, Reflect)]
+#[derive(Clone, PartialEq, Reflect)]
 pub struct Particle {
     pub(crate) transform: Transform,
     pub(crate) duration: f32,
     pub(crate) duration_fraction: f32,
     pub(crate) velocity: (Vec3, f32),
     pub(crate) color: LinearRgba,
-    pub(crate) frame: u32,
+    /// color of the emitting pixel, multiplied into the color curves
+    pub(crate) tint: LinearRgba,
+    /// current sprite frame, `None` plays the sheet over the lifetime
+    pub(crate) frame: Option<u32>,
+    /// offset into the frame range, from `random_start`
+    pub(crate) frame_start: u32,
     pub(crate) linear_acceleration: f32,
     pub(crate) linear_damp: f32,
     pub(crate) angular_acceleration: f32,
     pub(crate) angular_damp: f32,
     pub(crate) gravity_speed: f32,
     pub(crate) gravity_direction: Vec3,
+    /// stretch along the local up axis, from `velocity_stretch`
+    pub(crate) stretch: f32,
+    /// recent positions, newest first
+    pub(crate) trail: VecDeque<Vec3>,
+    pub(crate) trail_timer: f32,
+    /// values of the effects `custom_channels`
+    pub(crate) channels: Vec4,
+}
+
+impl Particle {
+    pub fn translation(&self) -> Vec3 {
+        self.transform.translation
+    }
+
+    /// progress over the lifetime, from 0 to 1
+    pub fn lifetime_fraction(&self) -> f32 {
+        self.duration_fraction
+    }
+
+    /// the custom channels, as seen by the shaders
+    pub fn channels(&self) -> Vec4 {
+        self.channels
+    }
+
+    /// writes a custom channel. Channels driven by a
+    /// curve are overwritten on the next update.
+    pub fn set_channel(&mut self, index: usize, value: f32) {
+        if index < 4 {
+            self.channels[index] = value;
+        }
+    }
 }
 
 pub(crate) fn clone_effect(
@@ -109,6 +283,7 @@ pub(crate) fn remove_finished_spawner(
         })
 }
 
+#[allow(clippy::type_complexity)]
 pub(crate) fn update_spawner(
     mut particles: Query<(
         Entity,
@@ -116,166 +291,689 @@ pub(crate) fn update_spawner(
         &mut ParticleSpawnerState,
         &ParticleEffectInstance,
         &GlobalTransform,
+        Option<&ViewVisibility>,
     )>,
     one_shots: Query<&OneShot>,
+    force_layers: Query<&ParticleForceLayers>,
     time: Res<Time<Virtual>>,
+    resources: SimulationResources,
 ) {
-    particles.par_iter_mut().for_each(
-        |(entity, mut store, mut state, effect_instance, transform)| {
-            if state.max_particles <= store.particles.len() as u32 {
-                return;
-            }
+    let context = SimulationContext {
+        colliders: &resources.colliders,
+        force_fields: &resources.force_fields,
+        image_masks: &resources.image_masks,
+        mesh_shapes: &resources.mesh_shapes,
+        polygons: &resources.polygons,
+        force_layers: u32::MAX,
+    };
 
+    particles.par_iter_mut().for_each(
+        |(entity, mut store, mut state, effect_instance, transform, visibility)| {
             let Some(effect) = &effect_instance.0 else {
                 return;
             };
 
+            let culled = !visibility.is_none_or(|visibility| visibility.get());
+            if culled && state.culling != CullingPolicy::AlwaysSimulate {
+                if let CullingPolicy::CatchUp(max) = state.culling {
+                    let max = Duration::from_secs_f32(max.max(0.));
+                    state.paused = state.paused.saturating_add(time.delta()).min(max);
+                }
+                // no distance emission along the path travelled while paused
+                state.previous_position = None;
+                return;
+            }
+
             let transform = transform.compute_transform();
+            let one_shot = one_shots.get(entity).is_ok();
+            let context = SimulationContext {
+                force_layers: force_layers.get(entity).copied().unwrap_or_default().0,
+                ..context
+            };
+
+            if !state.prewarmed
+                && emission::ready(
+                    &effect.emission_shape,
+                    context.image_masks,
+                    context.mesh_shapes,
+                )
+            {
+                state.prewarmed = true;
 
-            state
-                .timer
-                .set_duration(Duration::from_secs_f32(effect.spawn_rate));
-            state.timer.tick(time.delta());
+                fast_forward(
+                    &mut store,
+                    &mut state,
+                    effect,
+                    &transform,
+                    effect.prewarm.unwrap_or_default(),
+                    one_shot,
+                    &context,
+                );
+            }
 
-            if state.timer.is_finished() && state.active {
+            // spread over several frames, a long catch up would stall one
+            let paused = state.paused.min(MAX_CATCH_UP_PER_FRAME);
+            state.paused -= paused;
+            if !paused.is_zero() {
+                fast_forward(
+                    &mut store,
+                    &mut state,
+                    effect,
+                    &transform,
+                    paused.as_secs_f32(),
+                    one_shot,
+                    &context,
+                );
+            }
+
+            simulate_spawner(
+                &mut store,
+                &mut state,
+                effect,
+                &transform,
+                time.delta(),
+                one_shot,
+                &context,
+            );
+        },
+    );
+}
+
+/// simulates `seconds` in fixed steps, for prewarming and catching up
+fn fast_forward(
+    store: &mut ParticleStore,
+    state: &mut ParticleSpawnerState,
+    effect: &Particle2dEffect,
+    transform: &Transform,
+    seconds: f32,
+    one_shot: bool,
+    context: &SimulationContext,
+) {
+    let mut remaining = seconds;
+    while remaining > 0. {
+        let step = remaining.min(PREWARM_STEP);
+        simulate_spawner(
+            store,
+            state,
+            effect,
+            transform,
+            Duration::from_secs_f32(step),
+            one_shot,
+            context,
+        );
+        remaining -= step;
+    }
+}
+
+/// the movement of a spawner since the last step, partially applied
+/// to its particles in local and hybrid simulation space
+struct SpaceDelta {
+    affine: Affine3A,
+    rotation: Quat,
+    follow: f32,
+}
+
+impl SpaceDelta {
+    fn new(previous: &Transform, current: &Transform, follow: f32) -> Option<Self> {
+        if follow <= 0. || previous == current || previous.scale.cmpeq(Vec3::ZERO).any() {
+            return None;
+        }
+
+        Some(Self {
+            affine: current.compute_affine() * previous.compute_affine().inverse(),
+            rotation: Quat::IDENTITY.slerp(current.rotation * previous.rotation.inverse(), follow),
+            follow,
+        })
+    }
+
+    fn point(&self, point: Vec3) -> Vec3 {
+        point.lerp(self.affine.transform_point3(point), self.follow)
+    }
+
+    fn apply(&self, particle: &mut Particle) {
+        particle.transform.translation = self.point(particle.transform.translation);
+        particle.transform.rotation = self.rotation * particle.transform.rotation;
+
+        let velocity = &mut particle.velocity.0;
+        *velocity = velocity.lerp(self.affine.transform_vector3(*velocity), self.follow);
+
+        for position in particle.trail.iter_mut() {
+            *position = self.point(*position);
+        }
+    }
+}
+
+/// advances the emitter and all of its particles by `delta`
+fn simulate_spawner(
+    store: &mut ParticleStore,
+    state: &mut ParticleSpawnerState,
+    effect: &Particle2dEffect,
+    transform: &Transform,
+    delta: Duration,
+    one_shot: bool,
+    context: &SimulationContext,
+) {
+    // the emitter only emits between the start delay and the end of its duration.
+    let start = Duration::from_secs_f32(effect.start_delay.unwrap_or_default().max(0.));
+    let end = match (effect.emitter_duration, effect.looping.unwrap_or(true)) {
+        (Some(duration), false) => start + Duration::from_secs_f32(duration.max(0.)),
+        _ => Duration::MAX,
+    };
+
+    // the emitter waits for the image or mesh of its shape, one shots are not used up
+    let previous = state.elapsed;
+    if emission::ready(
+        &effect.emission_shape,
+        context.image_masks,
+        context.mesh_shapes,
+    ) {
+        state.elapsed = state.elapsed.saturating_add(delta);
+    }
+    let emit_delta = state.elapsed.min(end).saturating_sub(previous.max(start));
+
+    // emitter time of this step, relative to the start delay
+    let emit_from = previous.max(start).saturating_sub(start).as_secs_f64();
+    let emit_to = emit_from + emit_delta.as_secs_f64();
+    let cycle = effect
+        .emitter_duration
+        .map(|duration| duration as f64)
+        .filter(|duration| *duration > 0.);
+
+    let rate = match (&effect.spawn_rate_curve, cycle) {
+        (Some(curve), Some(cycle)) => curve.lerp((emit_to % cycle / cycle) as f32).max(0.),
+        _ => 1.,
+    };
+
+    let spawner_world_pos = transform.translation;
+    let previous_position = state
+        .previous_position
+        .replace(spawner_world_pos)
+        .unwrap_or(spawner_world_pos);
+    let movement = spawner_world_pos - previous_position;
+    if !delta.is_zero() {
+        state.velocity = movement / delta.as_secs_f32();
+    }
+    let inherited = state.velocity * effect.inherit_velocity.unwrap_or_default();
+
+    // local and hybrid space carry the living particles along with the spawner
+    let previous_transform = state
+        .previous_transform
+        .replace(*transform)
+        .unwrap_or(*transform);
+    let space = match (effect.simulation_space, effect.relative_positioning) {
+        (Some(space), _) => space,
+        (None, Some(true)) => SimulationSpace::Local,
+        _ => SimulationSpace::World,
+    };
+    let follow = match space {
+        SimulationSpace::World => 0.,
+        SimulationSpace::Local => 1.,
+        SimulationSpace::Hybrid(follow) => follow.clamp(0., 1.),
+    };
+    if let Some(space) = SpaceDelta::new(&previous_transform, transform, follow) {
+        store.iter_mut().for_each(|particle| space.apply(particle));
+    }
+    let spawn_distance = effect.spawn_distance.filter(|distance| *distance > 0.);
+
+    state
+        .timer
+        .set_duration(Duration::from_secs_f32(effect.spawn_rate));
+    let rate_delta = emit_delta.mul_f32(rate);
+    state.timer.tick(rate_delta);
+
+    let mut amount = 0;
+    if state.timer.is_finished() && !rate_delta.is_zero() && spawn_distance.is_none() {
+        // steps longer than the spawn rate, like prewarm and catch up, emit several times
+        let times = match state.timer.duration().is_zero() {
+            true => 1,
+            false => state.timer.times_finished_this_tick(),
+        };
+        amount += effect.spawn_amount.saturating_mul(times);
+    }
+
+    if let Some(bursts) = effect.bursts.as_ref().filter(|_| !emit_delta.is_zero()) {
+        amount += bursts
+            .iter()
+            .map(|burst| burst_amount(burst, emit_from, emit_to, cycle))
+            .sum::<u32>();
+    }
+
+    // thins out new particles, while over the particle budget.
+    // One shots emit in full, a thinned out one shot could emit nothing
+    let scale = match one_shot {
+        true => 1.,
+        false => state.emission_scale,
+    };
+    let thinned = |rng: &mut ParticleRng| scale < 1. && rng.random::<f32>() >= scale;
+
+    // at `max_particles`, skipped particles are not created at all
+    let max_particles = state.max_particles as usize;
+    let skip_new = state.overflow_policy == OverflowPolicy::SkipNew;
+    let full = |len: usize| skip_new && len >= max_particles;
+
+    let mut first_born = store.len();
+    if state.active && amount > 0 {
+        for _ in 0..amount {
+            if full(store.len()) {
+                break;
+            }
+            if thinned(&mut state.rng) {
+                continue;
+            }
+            store.extend(create_particle(
+                effect,
+                transform,
+                inherited,
+                context,
+                &mut state.rng,
+            ))
+        }
+    }
+
+    // distance based emission, evenly spaced along the travelled segment
+    if let Some(spawn_distance) = spawn_distance {
+        if state.active && !emit_delta.is_zero() {
+            let length = movement.length();
+            let mut along = spawn_distance - state.travelled;
+            state.travelled += length;
+
+            while state.travelled >= spawn_distance {
+                let mut at = *transform;
+                at.translation = previous_position + movement * (along / length);
                 for _ in 0..effect.spawn_amount {
-                    store.push(create_particle(effect, &transform))
+                    if full(store.len()) {
+                        break;
+                    }
+                    if thinned(&mut state.rng) {
+                        continue;
+                    }
+                    store.extend(create_particle(
+                        effect,
+                        &at,
+                        inherited,
+                        context,
+                        &mut state.rng,
+                    ))
                 }
+                along += spawn_distance;
+                state.travelled -= spawn_distance;
+            }
+        }
+    }
+
+    // one shots are used up by particles, not by attempts
+    let emitted = store.len() > first_born;
+
+    // recycles old particles for the new ones
+    if store.len() > max_particles {
+        let excess = store.len() - max_particles;
+        let mut victims = (0..first_born).collect::<Vec<_>>();
+        match state.overflow_policy {
+            OverflowPolicy::SkipNew => victims.clear(),
+            OverflowPolicy::RecycleOldest => victims.sort_by(|a, b| {
+                let age = |p: &Particle| p.duration_fraction * p.duration;
+                age(&store[*b]).total_cmp(&age(&store[*a]))
+            }),
+            OverflowPolicy::RecycleClosestToDeath => victims.sort_by(|a, b| {
+                let remaining = |p: &Particle| (1. - p.duration_fraction) * p.duration;
+                remaining(&store[*a]).total_cmp(&remaining(&store[*b]))
+            }),
+        }
+        victims.truncate(excess);
+
+        let mut recycle = vec![false; store.len()];
+        victims.iter().for_each(|index| recycle[*index] = true);
+        let mut index = 0;
+        store.retain(|_| {
+            index += 1;
+            !recycle[index - 1]
+        });
+        first_born -= victims.len();
+
+        // too few old particles to recycle, skip the newest
+        store.truncate(max_particles.max(first_born));
+    }
+
+    if emitted && one_shot {
+        state.active = false;
+    }
+
+    if state.elapsed >= end {
+        state.active = false;
+    }
+
+    let delta = delta.as_secs_f32();
+    let time = state.elapsed.as_secs_f32();
+
+    let sub_emitters = effect.sub_emitters.as_deref().unwrap_or_default();
+    let sub_emitters_on = |trigger: fn(&SubEmitterTrigger) -> bool| {
+        sub_emitters
+            .iter()
+            .filter(move |sub| trigger(&sub.trigger))
+            .filter_map(|sub| sub.handle.as_ref())
+    };
+
+    for handle in sub_emitters_on(|trigger| matches!(trigger, SubEmitterTrigger::Birth)) {
+        state.sub_emits.extend(
+            store[first_born..]
+                .iter()
+                .map(|particle| (handle.clone(), particle.transform.translation)),
+        );
+    }
+
+    let intervals = sub_emitters
+        .iter()
+        .filter_map(|sub| match (&sub.trigger, &sub.handle) {
+            (SubEmitterTrigger::Interval(interval), Some(handle)) if *interval > 0. => {
+                Some((handle, *interval))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let interval_emits = store.par_splat_map_mut(ComputeTaskPool::get(), None, |_, particles| {
+        let mut emits = Vec::new();
+        for particle in particles.iter_mut() {
+            let age = particle.duration_fraction * particle.duration;
+            particle
+                .duration_fraction
+                .add_assign(delta / particle.duration);
+            update_particle(particle, effect, delta, time, spawner_world_pos, context);
 
-                if one_shots.get(entity).is_ok() {
-                    state.active = false;
+            let next_age = particle.duration_fraction * particle.duration;
+            for (handle, interval) in intervals.iter() {
+                if (next_age / interval).floor() > (age / interval).floor() {
+                    emits.push(((*handle).clone(), particle.transform.translation));
                 }
             }
-            let delta = time.delta_secs();
-            let spawner_world_pos = transform.translation;
-
-            // Handle relative positioning
-            let position_delta = if effect.relative_positioning.unwrap_or(false) {
-                let current_pos = spawner_world_pos;
-                let delta = if let Some(prev_pos) = state.previous_position {
-                    current_pos - prev_pos
-                } else {
-                    Vec3::ZERO
-                };
-                state.previous_position = Some(current_pos);
-                delta
-            } else {
-                Vec3::ZERO
+        }
+        emits
+    });
+    state.sub_emits.extend(interval_emits.into_iter().flatten());
+
+    let deaths =
+        sub_emitters_on(|trigger| matches!(trigger, SubEmitterTrigger::Death)).collect::<Vec<_>>();
+    let sub_emits = &mut state.sub_emits;
+    store.retain(|particle| {
+        let alive = particle.duration_fraction < 1.0;
+        if !alive {
+            sub_emits.extend(
+                deaths
+                    .iter()
+                    .map(|handle| ((*handle).clone(), particle.transform.translation)),
+            );
+        }
+        alive
+    });
+
+    // every sub emit is a new entity, drop the ones above the limit
+    sub_emits.truncate(state.max_sub_emits as usize);
+}
+
+/// spawns the pending sub emitter effects, as one shot
+/// spawners with the material of their parent.
+pub(crate) fn spawn_sub_emitters<M: Particle2dMaterial>(
+    mut cmd: Commands,
+    mut spawners: Query<(&mut ParticleSpawnerState, &ParticleSpawner<M>)>,
+) {
+    spawners.iter_mut().for_each(|(mut state, spawner)| {
+        if state.sub_emits.is_empty() {
+            return;
+        }
+
+        // stops effects, that emit themselves
+        if state.depth >= MAX_SUB_EMITTER_DEPTH {
+            state.sub_emits.clear();
+            return;
+        }
+
+        let depth = state.depth + 1;
+        let mut sub_emits = std::mem::take(&mut state.sub_emits);
+        sub_emits.drain(..).for_each(|(effect, position)| {
+            // seeded by the parent, keeps the simulation reproducible
+            let child = ParticleSpawnerState {
+                depth,
+                ..ParticleSpawnerState::with_seed(state.rng.random())
             };
+            cmd.spawn((
+                ParticleSpawner(spawner.0.clone()),
+                ParticleEffectHandle(effect),
+                child,
+                OneShot::Despawn,
+                Transform::from_translation(position),
+            ));
+        });
+        state.sub_emits = sub_emits;
+    });
+}
 
-            store.par_splat_map_mut(ComputeTaskPool::get(), None, |_, particles| {
-                for particle in particles.iter_mut() {
-                    // position_delta should only be a value if relative_positioning is enabled
-                    particle.transform.translation += position_delta;
+/// amount of particles a burst emits in the emitter time `from..to`.
+/// Wraps around, if the emitter is looping.
+fn burst_amount(burst: &Burst, from: f64, to: f64, cycle: Option<f64>) -> u32 {
+    let Some(cycle) = cycle else {
+        return burst_amount_in_cycle(burst, from, to);
+    };
 
-                    particle
-                        .duration_fraction
-                        .add_assign(delta / particle.duration);
-                    update_particle(particle, effect, delta, spawner_world_pos);
-                }
-            });
-            store.retain(|particle| particle.duration_fraction < 1.0);
-        },
-    );
+    let first = (from / cycle).floor();
+    let last = (to / cycle).floor();
+    let (from, to) = (from - first * cycle, to - last * cycle);
+
+    if first == last {
+        return burst_amount_in_cycle(burst, from, to);
+    }
+
+    let full_cycles = (last - first - 1.) as u32;
+    burst_amount_in_cycle(burst, from, cycle)
+        + full_cycles * burst_amount_in_cycle(burst, 0., cycle)
+        + burst_amount_in_cycle(burst, 0., to)
+}
+
+fn burst_amount_in_cycle(burst: &Burst, from: f64, to: f64) -> u32 {
+    let time = burst.time as f64;
+    let interval = burst.interval as f64;
+    let cycles = burst.cycles.max(1);
+
+    if interval <= 0. {
+        return if (from..to).contains(&time) {
+            burst.count * cycles
+        } else {
+            0
+        };
+    }
+
+    let first = ((from - time) / interval).ceil().max(0.);
+    let last = (((to - time) / interval).ceil() - 1.).min((cycles - 1) as f64);
+
+    if last < first {
+        return 0;
+    }
+
+    (last - first + 1.) as u32 * burst.count
 }
 
-fn create_particle(effect: &Particle2dEffect, transform: &Transform) -> Particle {
+fn create_particle(
+    effect: &Particle2dEffect,
+    transform: &Transform,
+    inherited: Vec3,
+    context: &SimulationContext,
+    rng: &mut ParticleRng,
+) -> Option<Particle> {
     // direction
     let direction = effect
         .direction
         .as_ref()
-        .map(|m| m.rand())
+        .map(|m| m.rand(rng))
         .unwrap_or_default();
 
+    let point = emission::sample(
+        &effect.emission_shape,
+        effect.emission_mode.unwrap_or_default(),
+        context.image_masks,
+        context.mesh_shapes,
+        context.polygons,
+        rng,
+    )?;
+    let direction = match effect.emit_along_normal.unwrap_or(false) {
+        true if point.normal != Vec2::ZERO => direction.rotate(point.normal),
+        _ => direction,
+    };
+
     // apply local rotation
-    let direction = direction.rotate(transform.right().truncate());
+    let right = transform.right().truncate();
+    let direction = direction.rotate(right);
 
     // speed
     let speed = effect
         .linear_speed
         .as_ref()
-        .map(|s| s.rand())
+        .map(|s| s.rand(rng))
         .unwrap_or_default();
     // angular
     let angular = effect
         .angular_speed
         .as_ref()
-        .map(|s| s.rand())
+        .map(|s| s.rand(rng))
         .unwrap_or_default();
     // angular
-    let scale = effect.scale.as_ref().map(|s| s.rand()).unwrap_or_default();
+    let scale = effect
+        .scale
+        .as_ref()
+        .map(|s| s.rand(rng))
+        .unwrap_or_default();
 
     let gravity_direction = effect
         .gravity_direction
         .as_ref()
-        .map(|g| g.rand())
+        .map(|g| g.rand(rng))
         .unwrap_or_default()
         .extend(0.);
 
     let gravity_speed = effect
         .gravity_speed
         .as_ref()
-        .map(|g| g.rand())
+        .map(|g| g.rand(rng))
         .unwrap_or_default();
 
     let linear_damp = effect
         .linear_damp
         .as_ref()
-        .map(|d| d.rand())
+        .map(|d| d.rand(rng))
         .unwrap_or_default();
 
     let angular_damp = effect
         .angular_damp
         .as_ref()
-        .map(|a| a.rand())
+        .map(|a| a.rand(rng))
         .unwrap_or_default();
 
     let angular_acceleration = effect
         .angular_acceleration
         .as_ref()
-        .map(|a| a.rand())
+        .map(|a| a.rand(rng))
         .unwrap_or_default();
 
     let linear_acceleration = effect
         .linear_acceleration
         .as_ref()
-        .map(|a| a.rand())
+        .map(|a| a.rand(rng))
         .unwrap_or_default();
 
     let mut transform = *transform;
-    transform.scale = Vec3::splat(scale);
-
-    transform.translation += match effect.emission_shape {
-        EmissionShape::Point => Vec3::ZERO,
-        EmissionShape::Circle(radius) => {
-            Vec3::new(rand::random::<f32>() - 0.5, rand::random::<f32>() - 0.5, 0.)
-                .normalize_or_zero()
-                * radius
-                * rand::random::<f32>()
+    if !matches!(
+        effect.orientation.unwrap_or_default(),
+        ParticleOrientation::Fixed
+    ) {
+        if let Some(direction) = direction.try_normalize() {
+            transform.rotation = Quat::from_rotation_arc_2d(Vec2::Y, direction);
         }
+    }
+    transform.scale = match &effect.axis_scale {
+        Some((x, y)) => Vec3::new(x.rand(rng), y.rand(rng), 1.),
+        None => Vec3::splat(scale),
     };
 
-    Particle {
+    transform.translation += point.position.rotate(right).extend(0.);
+
+    let mut channels = Vec4::ZERO;
+    for (i, channel) in effect.custom_channels.iter().flatten().take(4).enumerate() {
+        channels[i] = match channel {
+            CustomChannel::Random(min, max) => min + (max - min) * rng.random::<f32>(),
+            CustomChannel::Curve(curve) => curve.lerp(0.),
+            CustomChannel::Value(value) => *value,
+        };
+    }
+
+    let frame_start = match &effect.sprite_animation {
+        Some(animation) if animation.random_start => {
+            rng.random_range(0..animation.frame_count.max(1))
+        }
+        _ => 0,
+    };
+
+    let base_color = effect.color.unwrap_or(LinearRgba::WHITE);
+    let tint = point.color.unwrap_or(LinearRgba::WHITE);
+    Some(Particle {
         transform,
-        velocity: ((direction * speed).extend(0.), angular),
+        velocity: ((direction * speed).extend(0.) + inherited, angular),
         duration_fraction: 0.0,
-        duration: effect.lifetime.rand(),
-        color: effect.color.unwrap_or(LinearRgba::WHITE),
+        duration: effect.lifetime.rand(rng),
+        color: tinted(base_color, tint),
+        tint,
         angular_damp,
         linear_damp,
         angular_acceleration,
         linear_acceleration,
         gravity_direction,
         gravity_speed,
-        frame: 0,
-    }
+        frame: effect
+            .sprite_animation
+            .as_ref()
+            .map(|animation| animation.first_frame + frame_start),
+        frame_start,
+        stretch: 1.,
+        trail: VecDeque::new(),
+        trail_timer: 0.,
+        channels,
+    })
+}
+
+fn tinted(color: LinearRgba, tint: LinearRgba) -> LinearRgba {
+    LinearRgba::from_vec4(color.to_vec4() * tint.to_vec4())
+}
+
+fn sprite_frame(animation: &SpriteAnimation, start: u32, progress: f32, age: f32) -> u32 {
+    let count = animation.frame_count.max(1);
+    let step = match animation.mode {
+        SpriteAnimationMode::OverLifetime => ((progress * count as f32) as u32).min(count - 1),
+        SpriteAnimationMode::Loop(fps) | SpriteAnimationMode::PingPong(fps) => {
+            (age * fps.max(0.)) as u32
+        }
+        SpriteAnimationMode::Static => 0,
+    };
+
+    let offset = match animation.mode {
+        SpriteAnimationMode::PingPong(_) if count > 1 => {
+            let period = 2 * count - 2;
+            let step = (start + step) % period;
+            if step < count {
+                step
+            } else {
+                period - step
+            }
+        }
+        _ => (start + step) % count,
+    };
+
+    animation.first_frame + offset
 }
 
 fn update_particle(
     particle: &mut Particle,
     effect: &Particle2dEffect,
     delta: f32,
+    time: f32,
     spawner_world_pos: Vec3,
+    context: &SimulationContext,
 ) {
     let (lin_velo, rot_velo) = &mut particle.velocity;
     let progress = particle.duration_fraction;
@@ -286,12 +984,37 @@ fn update_particle(
     *rot_velo = *rot_velo - progress * particle.angular_damp * *rot_velo * delta
         + progress * particle.angular_acceleration * *rot_velo * delta;
 
-    if let Some(scale_curve) = effect.scale_curve.as_ref() {
+    if let Some(axis_scale_curve) = effect.axis_scale_curve.as_ref() {
+        particle.transform.scale = axis_scale_curve.lerp(progress).extend(1.);
+    } else if let Some(scale_curve) = effect.scale_curve.as_ref() {
         particle.transform.scale = Vec3::splat(scale_curve.lerp(progress));
     }
 
     if let Some(color_curve) = effect.color_curve.as_ref() {
-        particle.color = color_curve.lerp(progress);
+        particle.color = tinted(color_curve.lerp(progress), particle.tint);
+    }
+
+    if let Some(alpha_curve) = effect.alpha_curve.as_ref() {
+        let alpha = match effect.color_curve {
+            Some(_) => particle.color.alpha,
+            None => effect.color.map_or(1., |color| color.alpha) * particle.tint.alpha,
+        };
+        particle.color.alpha = alpha * alpha_curve.lerp(progress);
+    }
+
+    for (i, channel) in effect.custom_channels.iter().flatten().take(4).enumerate() {
+        if let CustomChannel::Curve(curve) = channel {
+            particle.channels[i] = curve.lerp(progress);
+        }
+    }
+
+    if let Some(animation) = &effect.sprite_animation {
+        particle.frame = Some(sprite_frame(
+            animation,
+            particle.frame_start,
+            progress,
+            progress * particle.duration,
+        ));
     }
 
     // Apply attractor forces from effect
@@ -313,10 +1036,71 @@ fn update_particle(
         }
     }
 
-    let gravity = particle.gravity_direction * particle.gravity_speed * delta;
+    if let Some(turbulence) = &effect.turbulence {
+        let position = particle.transform.translation.truncate();
+        for field in turbulence.iter() {
+            *lin_velo += noise::turbulence_force(field, position, time).extend(0.) * delta;
+        }
+    }
+
+    if !context.force_fields.is_empty() {
+        *lin_velo += context
+            .force_fields
+            .force(particle.transform.translation, context.force_layers)
+            * delta;
+    }
+
+    let curve_at = |curve: &Option<curve::MultiCurve<f32>>| {
+        curve.as_ref().map_or(1., |curve| curve.lerp(progress))
+    };
+    let speed = curve_at(&effect.speed_curve);
+    let angular_speed = curve_at(&effect.angular_speed_curve);
+    let gravity =
+        particle.gravity_direction * particle.gravity_speed * curve_at(&effect.gravity_curve);
+    let velocity = *lin_velo * speed + gravity;
 
-    particle.transform.translation += *lin_velo * delta + gravity;
-    particle.transform.rotate_local_z(*rot_velo * delta);
+    let start = particle.transform.translation;
+    particle.transform.translation += velocity * delta;
+
+    match effect.orientation.unwrap_or_default() {
+        ParticleOrientation::AlignToVelocity => {
+            if let Some(direction) = velocity.truncate().try_normalize() {
+                particle.transform.rotation = Quat::from_rotation_arc_2d(Vec2::Y, direction);
+            }
+        }
+        _ => particle
+            .transform
+            .rotate_local_z(*rot_velo * angular_speed * delta),
+    }
+
+    if let Some(stretch) = effect.velocity_stretch {
+        particle.stretch = 1. + velocity.truncate().length() * stretch;
+    }
+
+    if let Some(settings) = effect
+        .collision
+        .as_ref()
+        .filter(|_| !context.colliders.is_empty())
+    {
+        let end = particle.transform.translation;
+        if let Some((position, velocity)) = collision::resolve_collision(
+            context.colliders,
+            settings,
+            start.truncate(),
+            end.truncate(),
+            lin_velo.truncate(),
+        ) {
+            if settings.kill_on_hit {
+                particle.duration_fraction = 1.0;
+            }
+            particle.transform.translation = position.extend(end.z);
+            *lin_velo = velocity.extend(lin_velo.z);
+        }
+    }
+
+    if let Some(trail_settings) = &effect.trail {
+        trail::record_trail(particle, trail_settings, delta);
+    }
 }
 
 pub(crate) fn calculate_particle_bounds(
@@ -347,3 +1131,157 @@ pub(crate) fn calculate_particle_bounds(
         cmd.entity(entity).try_insert(aabb);
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmissionShape, Rval};
+    use rand::RngCore;
+
+    /// runs the effect for `steps` frames, `check` sees the store after each one
+    fn run(
+        effect: &Particle2dEffect,
+        state: &mut ParticleSpawnerState,
+        steps: usize,
+        mut check: impl FnMut(&ParticleStore),
+    ) -> ParticleStore {
+        ComputeTaskPool::get_or_init(Default::default);
+
+        let colliders = ParticleColliders::default();
+        let force_fields = ParticleForceFields::default();
+        let image_masks = ParticleImageMasks::default();
+        let mesh_shapes = ParticleMeshShapes::default();
+        let polygons = ParticlePolygons::default();
+        let context = SimulationContext {
+            colliders: &colliders,
+            force_fields: &force_fields,
+            image_masks: &image_masks,
+            mesh_shapes: &mesh_shapes,
+            polygons: &polygons,
+            force_layers: u32::MAX,
+        };
+
+        let mut store = ParticleStore::default();
+        for _ in 0..steps {
+            simulate_spawner(
+                &mut store,
+                state,
+                effect,
+                &Transform::default(),
+                Duration::from_secs_f32(1. / 60.),
+                false,
+                &context,
+            );
+            check(&store);
+        }
+        store
+    }
+
+    fn simulate(seed: u64) -> ParticleStore {
+        let effect = Particle2dEffect {
+            spawn_rate: 0.1,
+            spawn_amount: 8,
+            emission_shape: EmissionShape::Circle(20.),
+            lifetime: Rval::new(2., 0.5),
+            linear_speed: Some(Rval::new(100., 0.5)),
+            direction: Some(Rval::new(Vec2::Y, 0.5)),
+            angular_speed: Some(Rval::new(1., 1.)),
+            scale: Some(Rval::new(2., 0.5)),
+            ..Default::default()
+        };
+
+        run(
+            &effect,
+            &mut ParticleSpawnerState::with_seed(seed),
+            60,
+            |_| (),
+        )
+    }
+
+    #[test]
+    fn seeds_are_pinned() {
+        // replays and golden images depend on this, never change it
+        let mut rng = ParticleRng::seeded(42);
+        assert_eq!(rng.next_u64(), 12578764544318200737);
+    }
+
+    #[test]
+    fn same_seed_same_particles() {
+        let store = simulate(42);
+        assert!(!store.is_empty());
+        assert!(store == simulate(42));
+        assert!(store != simulate(7));
+    }
+
+    fn burst(time: f32, count: u32, cycles: u32, interval: f32) -> Burst {
+        Burst {
+            time,
+            count,
+            cycles,
+            interval,
+        }
+    }
+
+    #[test]
+    fn burst_in_step() {
+        let single = burst(1., 5, 1, 0.);
+        assert_eq!(burst_amount(&single, 0., 1., None), 0);
+        assert_eq!(burst_amount(&single, 1., 2., None), 5);
+        assert_eq!(burst_amount(&single, 0.5, 1., None), 0);
+
+        // without an interval, all cycles fire at once
+        let stacked = burst(1., 5, 3, 0.);
+        assert_eq!(burst_amount(&stacked, 0.9, 1.1, None), 15);
+        let stacked = burst(1., 5, 3, -1.);
+        assert_eq!(burst_amount(&stacked, 0.9, 1.1, None), 15);
+
+        let repeated = burst(1., 5, 3, 0.5);
+        assert_eq!(burst_amount(&repeated, 0., 1.6, None), 10);
+        assert_eq!(burst_amount(&repeated, 1.5, 3., None), 10);
+        assert_eq!(burst_amount(&repeated, 0., 10., None), 15);
+    }
+
+    #[test]
+    fn burst_across_cycles() {
+        let single = burst(0.5, 1, 1, 0.);
+        assert_eq!(burst_amount(&single, 1.9, 2.6, Some(2.)), 1);
+        assert_eq!(burst_amount(&single, 0.4, 6.6, Some(2.)), 4);
+
+        // a burst at the start of the cycle fires on the wrap
+        let start = burst(0., 1, 1, 0.);
+        assert_eq!(burst_amount(&start, 0., 2., Some(2.)), 1);
+        assert_eq!(burst_amount(&start, 1.9, 2.1, Some(2.)), 1);
+        assert_eq!(burst_amount(&start, 2., 4., Some(2.)), 1);
+    }
+
+    fn recycles_for_more_new_than_old(policy: OverflowPolicy) {
+        // every frame emits more particles than fit, only this frame's survive
+        let effect = Particle2dEffect {
+            spawn_rate: 0.,
+            spawn_amount: 25,
+            lifetime: Rval::new(10., 0.5),
+            ..Default::default()
+        };
+        let mut state = ParticleSpawnerState {
+            max_particles: 10,
+            overflow_policy: policy,
+            ..ParticleSpawnerState::with_seed(42)
+        };
+
+        let age = |particle: &Particle| particle.duration_fraction * particle.duration;
+        run(&effect, &mut state, 10, |store| {
+            assert_eq!(store.len(), 10);
+            assert!(store.iter().all(|particle| age(particle) < 1.5 / 60.));
+        });
+    }
+
+    #[test]
+    fn recycle_oldest_overflow() {
+        recycles_for_more_new_than_old(OverflowPolicy::RecycleOldest);
+    }
+
+    #[test]
+    fn recycle_closest_to_death_overflow() {
+        recycles_for_more_new_than_old(OverflowPolicy::RecycleClosestToDeath);
+    }
+}
@@ -1,4 +1,6 @@
 use bevy_math::Vec2;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Clone, Debug, Serialize, Default)]
@@ -11,18 +13,18 @@ impl<V> Rval<V> {
 }
 
 pub trait Random<V> {
-    fn rand(&self) -> V;
+    fn rand<R: Rng + ?Sized>(&self, rng: &mut R) -> V;
 }
 
 impl Random<Vec2> for Rval<Vec2> {
-    fn rand(&self) -> Vec2 {
+    fn rand<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec2 {
         // Skip, if not needed.
         if self.1 <= 0.0001 {
             return self.0;
         }
 
         let max_angle = 2. * std::f32::consts::PI * self.1;
-        let random_angle = (rand::random::<f32>() - 0.5) * max_angle;
+        let random_angle = (rng.random::<f32>() - 0.5) * max_angle;
 
         let (sin, cos) = random_angle.sin_cos();
         Vec2::new(
@@ -33,8 +35,45 @@ impl Random<Vec2> for Rval<Vec2> {
 }
 
 impl Random<f32> for Rval<f32> {
-    fn rand(&self) -> f32 {
-        let r = (rand::random::<f32>() - 0.5) * 2. * self.1;
+    fn rand<R: Rng + ?Sized>(&self, rng: &mut R) -> f32 {
+        let r = (rng.random::<f32>() - 0.5) * 2. * self.1;
         self.0 + self.0 * r
     }
 }
+
+/// Seedable random number generator, owned by each spawner.
+/// Every random draw of the simulation goes through it, so the same
+/// seed and the same time steps always produce the same particles.
+/// ChaCha8 is a fixed algorithm, seeds stay valid across rand versions.
+#[derive(Clone, Debug)]
+pub struct ParticleRng(ChaCha8Rng);
+
+impl ParticleRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+/// picks a random seed from the thread rng.
+impl Default for ParticleRng {
+    fn default() -> Self {
+        Self(ChaCha8Rng::from_rng(&mut rand::rng()))
+    }
+}
+
+impl RngCore for ParticleRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.0.fill_bytes(dst)
+    }
+}
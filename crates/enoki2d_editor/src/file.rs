@@ -69,7 +69,8 @@ fn file_drop(
                     CompressedImageFormats::NONE,
                     false,
                     ImageSampler::nearest(),
-                    RenderAssetUsages::RENDER_WORLD,
+                    // image masks read the pixels on the cpu
+                    RenderAssetUsages::default(),
                 ) {
                     Ok(img) => img,
                     Err(err) => {
@@ -197,7 +198,8 @@ pub fn open_load_image_dialog(
                     CompressedImageFormats::NONE,
                     false,
                     ImageSampler::nearest(),
-                    RenderAssetUsages::RENDER_WORLD,
+                    // image masks read the pixels on the cpu
+                    RenderAssetUsages::default(),
                 ) {
                     Ok(img) => img,
                     Err(err) => {
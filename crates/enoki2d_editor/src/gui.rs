@@ -90,6 +90,13 @@ pub(crate) fn scene_gui(ui: &mut Ui, settings: &mut SceneSettings) {
 enum Shape {
     Point,
     Circle,
+    Rect,
+    Ring,
+    Arc,
+    Line,
+    Polygon,
+    Image,
+    Mesh,
 }
 
 impl From<Shape> for &'static str {
@@ -97,6 +104,13 @@ impl From<Shape> for &'static str {
         match val {
             Shape::Point => "Point",
             Shape::Circle => "Circle",
+            Shape::Rect => "Rect",
+            Shape::Ring => "Ring",
+            Shape::Arc => "Arc",
+            Shape::Line => "Line",
+            Shape::Polygon => "Polygon",
+            Shape::Image => "Image",
+            Shape::Mesh => "Mesh",
         }
     }
 }
@@ -113,6 +127,13 @@ impl From<&EmissionShape> for Shape {
         match value {
             EmissionShape::Point => Self::Point,
             EmissionShape::Circle(_) => Self::Circle,
+            EmissionShape::Rect(_) => Self::Rect,
+            EmissionShape::Ring { .. } => Self::Ring,
+            EmissionShape::Arc { .. } => Self::Arc,
+            EmissionShape::Line(..) => Self::Line,
+            EmissionShape::Polygon(_) => Self::Polygon,
+            EmissionShape::Image(_) => Self::Image,
+            EmissionShape::Mesh(_) => Self::Mesh,
         }
     }
 }
@@ -150,10 +171,46 @@ pub(crate) fn config_gui(
         slider_field(ui, "Amount", &mut effect.spawn_amount, 1..=1000000);
         slider_field(ui, "Spawn rate", &mut effect.spawn_rate, (0.01)..=120.);
 
-        // Relative positioning checkbox
-        let mut relative_positioning = effect.relative_positioning.unwrap_or(false);
-        ui.checkbox(&mut relative_positioning, "Relative Positioning");
-        effect.relative_positioning = Some(relative_positioning);
+        // relative positioning is saved as local space
+        let mut space = match effect.relative_positioning.take() {
+            Some(true) => effect.simulation_space.unwrap_or(SimulationSpace::Local),
+            _ => effect.simulation_space.unwrap_or_default(),
+        };
+        let follow = match space {
+            SimulationSpace::Hybrid(follow) => follow,
+            _ => 0.5,
+        };
+        ui.label("Simulation space");
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut space, SimulationSpace::World, "World");
+            ui.selectable_value(&mut space, SimulationSpace::Local, "Local");
+            ui.selectable_value(&mut space, SimulationSpace::Hybrid(follow), "Hybrid");
+        });
+        if let SimulationSpace::Hybrid(follow) = &mut space {
+            ui.add(slider(follow, 0.0..=1.).text("Follow"));
+        }
+        effect.simulation_space = Some(space);
+
+        let mut start_delay = effect.start_delay.unwrap_or_default();
+        slider_field(ui, "Start delay", &mut start_delay, 0.0..=60.);
+        effect.start_delay = (start_delay > 0.).then_some(start_delay);
+
+        let mut prewarm = effect.prewarm.unwrap_or_default();
+        slider_field(ui, "Prewarm", &mut prewarm, 0.0..=60.);
+        effect.prewarm = (prewarm > 0.).then_some(prewarm);
+
+        let mut limited = effect.emitter_duration.is_some();
+        ui.checkbox(&mut limited, "Limited duration");
+        if let Some(mut duration) = effect.emitter_duration.filter(|_| limited) {
+            slider_field(ui, "Duration", &mut duration, (0.01)..=120.);
+            effect.emitter_duration = Some(duration);
+
+            let mut looping = effect.looping.unwrap_or(true);
+            ui.checkbox(&mut looping, "Looping");
+            effect.looping = Some(looping);
+        } else {
+            effect.emitter_duration = limited.then_some(1.0);
+        }
 
         ui.label("Emission type");
 
@@ -166,24 +223,140 @@ pub(crate) fn config_gui(
                 .show_ui(ui, |ui| {
                     ui.selectable_value(&mut shape, Shape::Point, Shape::Point);
                     ui.selectable_value(&mut shape, Shape::Circle, Shape::Circle);
+                    ui.selectable_value(&mut shape, Shape::Rect, Shape::Rect);
+                    ui.selectable_value(&mut shape, Shape::Ring, Shape::Ring);
+                    ui.selectable_value(&mut shape, Shape::Arc, Shape::Arc);
+                    ui.selectable_value(&mut shape, Shape::Line, Shape::Line);
+                    ui.selectable_value(&mut shape, Shape::Polygon, Shape::Polygon);
+                    ui.selectable_value(&mut shape, Shape::Image, Shape::Image);
+                    ui.selectable_value(&mut shape, Shape::Mesh, Shape::Mesh);
                 });
             if before != shape {
                 effect.emission_shape = match shape {
                     Shape::Point => EmissionShape::Point,
                     Shape::Circle => EmissionShape::Circle(15.0),
+                    Shape::Rect => EmissionShape::Rect(Vec2::splat(30.)),
+                    Shape::Ring => EmissionShape::Ring {
+                        inner: 10.,
+                        outer: 15.,
+                    },
+                    Shape::Arc => EmissionShape::Arc {
+                        radius: 15.,
+                        start: 0.,
+                        end: std::f32::consts::PI,
+                    },
+                    Shape::Line => EmissionShape::Line(Vec2::new(-15., 0.), Vec2::new(15., 0.)),
+                    Shape::Polygon => EmissionShape::Polygon(vec![
+                        Vec2::new(-15., -15.),
+                        Vec2::new(15., -15.),
+                        Vec2::new(0., 15.),
+                    ]),
+                    Shape::Image => EmissionShape::Image(ImageMask {
+                        path: None,
+                        handle: None,
+                        channel: MaskChannel::Alpha,
+                        threshold: 0.5,
+                        pixel_size: 1.,
+                        use_color: true,
+                    }),
+                    Shape::Mesh => EmissionShape::Mesh(MeshShape {
+                        path: None,
+                        handle: None,
+                        scale: Vec2::ONE,
+                    }),
                 };
-            } else {
-                let mut val = match effect.emission_shape {
-                    EmissionShape::Point => {
-                        return;
-                    }
-                    EmissionShape::Circle(val) => val,
-                };
-                if ui.add(egui::DragValue::new(&mut val)).changed() {
-                    effect.emission_shape = EmissionShape::Circle(val);
-                }
             }
         });
+
+        match &mut effect.emission_shape {
+            EmissionShape::Point => (),
+            EmissionShape::Circle(radius) => {
+                slider_field(ui, "Radius", radius, 0.1..=1000.);
+            }
+            EmissionShape::Rect(size) => {
+                slider_field(ui, "Width", &mut size.x, 0.1..=1000.);
+                slider_field(ui, "Height", &mut size.y, 0.1..=1000.);
+            }
+            EmissionShape::Ring { inner, outer } => {
+                slider_field(ui, "Inner", inner, 0.0..=1000.);
+                slider_field(ui, "Outer", outer, 0.1..=1000.);
+            }
+            EmissionShape::Arc { radius, start, end } => {
+                slider_field(ui, "Radius", radius, 0.1..=1000.);
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(start).speed(0.01).prefix("start: "));
+                    ui.add(egui::DragValue::new(end).speed(0.01).prefix("end: "));
+                });
+            }
+            EmissionShape::Line(start, end) => {
+                vec2_drag(ui, "Start", start);
+                vec2_drag(ui, "End", end);
+            }
+            EmissionShape::Polygon(points) => {
+                let mut remove = None;
+                for (i, point) in points.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        vec2_drag(ui, &format!("#{i}"), point);
+                        if ui.small_button("x").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    points.remove(i);
+                }
+                if ui.button("Add Point").clicked() {
+                    points.push(points.last().copied().unwrap_or_default() + Vec2::X * 10.);
+                }
+            }
+            EmissionShape::Image(mask) => {
+                // previews with the loaded texture
+                if mask.handle.is_none() {
+                    mask.handle = Some(crate::file::SPRITE_TEXTURE);
+                }
+                ui.label("Emits from the loaded texture");
+
+                let mut path = mask.path.clone().unwrap_or_default();
+                ui.horizontal(|ui| {
+                    ui.label("Asset path");
+                    ui.text_edit_singleline(&mut path);
+                });
+                mask.path = (!path.is_empty()).then_some(path);
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut mask.channel, MaskChannel::Alpha, "Alpha");
+                    ui.selectable_value(&mut mask.channel, MaskChannel::Luminance, "Luminance");
+                });
+                ui.add(slider(&mut mask.threshold, 0.0..=1.).text("Threshold"));
+                slider_field(ui, "Pixel size", &mut mask.pixel_size, 0.01..=100.);
+                ui.checkbox(&mut mask.use_color, "Use pixel color");
+            }
+            EmissionShape::Mesh(shape) => {
+                ui.label("Meshes are not previewed in the editor");
+
+                let mut path = shape.path.clone().unwrap_or_default();
+                ui.horizontal(|ui| {
+                    ui.label("Asset path");
+                    ui.text_edit_singleline(&mut path);
+                });
+                shape.path = (!path.is_empty()).then_some(path);
+                vec2_drag(ui, "Scale", &mut shape.scale);
+            }
+        }
+
+        if !matches!(effect.emission_shape, EmissionShape::Point) {
+            let mut mode = effect.emission_mode.unwrap_or_default();
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut mode, EmissionMode::Volume, "Volume");
+                ui.selectable_value(&mut mode, EmissionMode::Edge, "Edge");
+            });
+            effect.emission_mode = Some(mode);
+
+            let mut along_normal = effect.emit_along_normal.unwrap_or(false);
+            ui.checkbox(&mut along_normal, "Emit along normal");
+            effect.emit_along_normal = Some(along_normal);
+        }
+
         rval_f32_field(ui, "Lifetime", &mut effect.lifetime);
     });
     ui.separator();
@@ -211,9 +384,44 @@ pub(crate) fn config_gui(
         } else {
             effect.linear_acceleration = Some(Rval::default());
         }
+
+        let mut stretch = effect.velocity_stretch.unwrap_or_default();
+        slider_field(ui, "Stretch", &mut stretch, 0.0..=1.);
+        effect.velocity_stretch = (stretch > 0.).then_some(stretch);
+
+        let mut inherit = effect.inherit_velocity.unwrap_or_default();
+        slider_field(ui, "Inherit", &mut inherit, 0.0..=2.);
+        effect.inherit_velocity = (inherit > 0.).then_some(inherit);
+
+        if let Some(curve) = effect.speed_curve.as_mut() {
+            ui.label("Speed Curve");
+            curve_field_f32(ui, "speed", curve);
+            if curve.points.is_empty() {
+                effect.speed_curve = None;
+            }
+        } else if ui.button("Add Speed Curve").clicked() {
+            effect.speed_curve = Some(multiplier_curve());
+        }
     });
     ui.separator();
     collapsing_header("Angular velocity").show(ui, |ui| {
+        let mut orientation = effect.orientation.unwrap_or_default();
+        ui.label("Orientation");
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut orientation, ParticleOrientation::Fixed, "Fixed");
+            ui.selectable_value(
+                &mut orientation,
+                ParticleOrientation::AlignToVelocity,
+                "Velocity",
+            );
+            ui.selectable_value(
+                &mut orientation,
+                ParticleOrientation::AlignToEmitterDirection,
+                "Emitter",
+            );
+        });
+        effect.orientation = Some(orientation);
+
         if let Some(mut speed) = effect.angular_speed.as_mut() {
             rval_f32_field(ui, "Speed", speed);
         } else {
@@ -231,6 +439,16 @@ pub(crate) fn config_gui(
         } else {
             effect.angular_acceleration = Some(Rval::default());
         }
+
+        if let Some(curve) = effect.angular_speed_curve.as_mut() {
+            ui.label("Speed Curve");
+            curve_field_f32(ui, "angular_speed", curve);
+            if curve.points.is_empty() {
+                effect.angular_speed_curve = None;
+            }
+        } else if ui.button("Add Speed Curve").clicked() {
+            effect.angular_speed_curve = Some(multiplier_curve());
+        }
     });
 
     ui.separator();
@@ -246,6 +464,16 @@ pub(crate) fn config_gui(
         } else {
             effect.gravity_speed = Some(Rval::default());
         }
+
+        if let Some(curve) = effect.gravity_curve.as_mut() {
+            ui.label("Strength Curve");
+            curve_field_f32(ui, "gravity", curve);
+            if curve.points.is_empty() {
+                effect.gravity_curve = None;
+            }
+        } else if ui.button("Add Strength Curve").clicked() {
+            effect.gravity_curve = Some(multiplier_curve());
+        }
     });
 
     ui.separator();
@@ -327,10 +555,197 @@ pub(crate) fn config_gui(
         }
     });
 
+    ui.separator();
+    collapsing_header("Turbulence").show(ui, |ui| {
+        if let Some(turbulence) = effect.turbulence.as_mut() {
+            let mut to_remove: Option<usize> = None;
+
+            for (i, field) in turbulence.iter_mut().enumerate() {
+                egui::Grid::new(format!("turbulence_{i}"))
+                    .spacing([4., 4.])
+                    .min_col_width(80.)
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label(format!("Field {}", i + 1));
+
+                        if ui.button("🗑").clicked() {
+                            to_remove = Some(i);
+                        }
+                        ui.end_row();
+
+                        ui.label("Kind");
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(&mut field.kind, TurbulenceKind::Curl, "Curl");
+                            ui.selectable_value(&mut field.kind, TurbulenceKind::Value, "Value");
+                        });
+                        ui.end_row();
+
+                        ui.label("Frequency");
+                        ui.add(slider(&mut field.frequency, 0.0001..=1.0).logarithmic(true));
+                        ui.end_row();
+
+                        ui.label("Strength");
+                        ui.add(slider(&mut field.strength, 0.0..=10000.0).logarithmic(true));
+                        ui.end_row();
+
+                        ui.label("Octaves");
+                        ui.add(slider(&mut field.octaves, 1..=6));
+                        ui.end_row();
+
+                        ui.label("Scroll");
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut field.scroll.x)
+                                    .prefix("X: ")
+                                    .speed(1.0),
+                            );
+                            ui.add(
+                                egui::DragValue::new(&mut field.scroll.y)
+                                    .prefix("Y: ")
+                                    .speed(1.0),
+                            );
+                        });
+                        ui.end_row();
+                    });
+                ui.separator();
+            }
+
+            if let Some(index) = to_remove {
+                turbulence.remove(index);
+            }
+
+            if ui.button("Add Field").clicked() {
+                turbulence.push(default_turbulence());
+            }
+
+            if turbulence.is_empty() {
+                effect.turbulence = None;
+            }
+        } else {
+            ui.label("No turbulence defined");
+            if ui.button("Add First Field").clicked() {
+                effect.turbulence = Some(vec![default_turbulence()]);
+            }
+        }
+    });
+
+    ui.separator();
+    collapsing_header("Trail").show(ui, |ui| {
+        let mut enabled = effect.trail.is_some();
+        ui.checkbox(&mut enabled, "Enabled");
+
+        if !enabled {
+            effect.trail = None;
+            return;
+        }
+
+        let trail = effect.trail.get_or_insert_with(|| Trail {
+            length: 10,
+            interval: 0.02,
+            width: 4.0,
+            width_curve: None,
+            color_curve: None,
+        });
+
+        slider_field(ui, "Length", &mut trail.length, 2..=100);
+        slider_field(ui, "Interval", &mut trail.interval, 0.001..=1.);
+        slider_field(ui, "Width", &mut trail.width, 0.1..=200.);
+
+        if let Some(curve) = trail.width_curve.as_mut() {
+            ui.label("Width Curve");
+            curve_field_f32(ui, "trail_width", curve);
+            if curve.points.is_empty() {
+                trail.width_curve = None;
+            }
+        } else if ui.button("Add Width Curve").clicked() {
+            trail.width_curve = Some(multiplier_curve());
+        }
+    });
+
+    ui.separator();
+    collapsing_header("Sprite Animation").show(ui, |ui| {
+        let mut enabled = effect.sprite_animation.is_some();
+        ui.checkbox(&mut enabled, "Enabled");
+
+        if !enabled {
+            effect.sprite_animation = None;
+            return;
+        }
+
+        let animation = effect
+            .sprite_animation
+            .get_or_insert_with(|| SpriteAnimation {
+                first_frame: 0,
+                frame_count: 1,
+                mode: SpriteAnimationMode::OverLifetime,
+                random_start: false,
+            });
+
+        slider_field(ui, "First frame", &mut animation.first_frame, 0..=64);
+        slider_field(ui, "Frame count", &mut animation.frame_count, 1..=64);
+        ui.checkbox(&mut animation.random_start, "Random start");
+
+        let fps = match animation.mode {
+            SpriteAnimationMode::Loop(fps) | SpriteAnimationMode::PingPong(fps) => fps,
+            _ => 12.,
+        };
+        ui.horizontal(|ui| {
+            ui.selectable_value(
+                &mut animation.mode,
+                SpriteAnimationMode::OverLifetime,
+                "Lifetime",
+            );
+            ui.selectable_value(&mut animation.mode, SpriteAnimationMode::Loop(fps), "Loop");
+            ui.selectable_value(
+                &mut animation.mode,
+                SpriteAnimationMode::PingPong(fps),
+                "PingPong",
+            );
+            ui.selectable_value(&mut animation.mode, SpriteAnimationMode::Static, "Static");
+        });
+
+        match &mut animation.mode {
+            SpriteAnimationMode::Loop(fps) | SpriteAnimationMode::PingPong(fps) => {
+                slider_field(ui, "Fps", fps, 0.1..=120.);
+            }
+            _ => {}
+        }
+    });
+
     ui.separator();
     collapsing_header("Scale").show(ui, |ui| {
+        let mut per_axis = effect.axis_scale.is_some() || effect.axis_scale_curve.is_some();
+        ui.checkbox(&mut per_axis, "Per axis");
+
+        if !per_axis {
+            effect.axis_scale = None;
+            effect.axis_scale_curve = None;
+        } else if let Some(axis_scale_curve) = effect.axis_scale_curve.as_mut() {
+            curve_field_vec2(ui, "axis_scale", axis_scale_curve);
+            if axis_scale_curve.points.is_empty() {
+                effect.axis_scale_curve = None;
+            }
+            return;
+        } else {
+            if let Some((x, y)) = effect.axis_scale.as_mut() {
+                rval_f32_field(ui, "Init Scale X", x);
+                rval_f32_field(ui, "Init Scale Y", y);
+            } else {
+                let scale = effect.scale.clone().unwrap_or_default();
+                effect.axis_scale = Some((scale.clone(), scale));
+            }
+
+            if ui.button("Add Scale Curve").clicked() {
+                let curve = bevy_enoki::prelude::MultiCurve::new()
+                    .with_point(Vec2::ONE, 0.0, None)
+                    .with_point(Vec2::ONE, 1.0, None);
+                effect.axis_scale_curve = Some(curve);
+            }
+            return;
+        }
+
         if let Some(scale_curve) = effect.scale_curve.as_mut() {
-            curve_field_f32(ui, scale_curve);
+            curve_field_f32(ui, "scale", scale_curve);
             if scale_curve.points.is_empty() {
                 effect.scale_curve = None;
             }
@@ -372,6 +787,41 @@ pub(crate) fn config_gui(
                 effect.color_curve = Some(curve);
             }
         }
+
+        if let Some(curve) = effect.alpha_curve.as_mut() {
+            ui.label("Alpha Curve");
+            curve_field_f32(ui, "alpha", curve);
+            if curve.points.is_empty() {
+                effect.alpha_curve = None;
+            }
+        } else if ui.button("Add Alpha Curve").clicked() {
+            effect.alpha_curve = Some(multiplier_curve());
+        }
+    });
+}
+
+/// flat curve, that keeps the value as is
+fn multiplier_curve() -> bevy_enoki::prelude::MultiCurve<f32> {
+    bevy_enoki::prelude::MultiCurve::new()
+        .with_point(1.0, 0.0, None)
+        .with_point(1.0, 1.0, None)
+}
+
+fn default_turbulence() -> Turbulence {
+    Turbulence {
+        kind: TurbulenceKind::Curl,
+        frequency: 0.01,
+        strength: 100.0,
+        octaves: 2,
+        scroll: Vec2::ZERO,
+    }
+}
+
+fn vec2_drag(ui: &mut Ui, label: &str, field: &mut Vec2) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.add(egui::DragValue::new(&mut field.x).prefix("x: "));
+        ui.add(egui::DragValue::new(&mut field.y).prefix("y: "));
     });
 }
 
@@ -426,7 +876,7 @@ fn rval_vec2_field(ui: &mut Ui, label: &str, field: &mut Rval<Vec2>) {
     ui.add_space(5.0);
 }
 
-fn curve_field_f32(ui: &mut Ui, curve: &mut bevy_enoki::prelude::MultiCurve<f32>) {
+fn curve_field_f32(ui: &mut Ui, id: &str, curve: &mut bevy_enoki::prelude::MultiCurve<f32>) {
     let sin: PlotPoints = (0..100)
         .map(|i| {
             let x = i as f64 * 0.01;
@@ -435,7 +885,7 @@ fn curve_field_f32(ui: &mut Ui, curve: &mut bevy_enoki::prelude::MultiCurve<f32>
         })
         .collect();
     let line = Line::new("sin", sin);
-    egui_plot::Plot::new("curve_f32")
+    egui_plot::Plot::new(format!("{id}_curve_f32"))
         .height(100.)
         .allow_drag(false)
         .allow_double_click_reset(false)
@@ -456,7 +906,7 @@ fn curve_field_f32(ui: &mut Ui, curve: &mut bevy_enoki::prelude::MultiCurve<f32>
         .iter_mut()
         .enumerate()
         .for_each(|(i, (val, pos, easing))| {
-            egui::Grid::new(format!("p_{i}")).show(ui, |ui| {
+            egui::Grid::new(format!("{id}_p_{i}")).show(ui, |ui| {
                 ui.label("value");
                 ui.add(slider(val, 0.0..=9999.0).logarithmic(true));
                 if ui.button("Delete").clicked() {
@@ -473,7 +923,7 @@ fn curve_field_f32(ui: &mut Ui, curve: &mut bevy_enoki::prelude::MultiCurve<f32>
                 };
                 ui.add(slider(pos, (start_pos)..=max_pos));
                 if i != 0 {
-                    easing_select(ui, format!("easing_{i}"), easing);
+                    easing_select(ui, format!("{id}_easing_{i}"), easing);
                 }
             });
 
@@ -491,6 +941,77 @@ fn curve_field_f32(ui: &mut Ui, curve: &mut bevy_enoki::prelude::MultiCurve<f32>
     }
 }
 
+fn curve_field_vec2(ui: &mut Ui, id: &str, curve: &mut bevy_enoki::prelude::MultiCurve<Vec2>) {
+    let axis_line = |name: &str, axis: fn(Vec2) -> f32| {
+        let points: PlotPoints = (0..100)
+            .map(|i| {
+                let x = i as f64 * 0.01;
+                let y = axis(curve.lerp(x as f32));
+                [x, y as f64]
+            })
+            .collect();
+        Line::new(name, points)
+    };
+    let x_line = axis_line("x", |v| v.x);
+    let y_line = axis_line("y", |v| v.y);
+
+    egui_plot::Plot::new(format!("{id}_curve_vec2"))
+        .height(100.)
+        .allow_drag(false)
+        .allow_double_click_reset(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .show(ui, |ui| {
+            ui.line(x_line);
+            ui.line(y_line);
+        });
+
+    let mut remove = Vec::new();
+    let curve_pos: Vec<f32> = curve.points.iter().map(|(_, pos, _)| *pos).collect();
+    curve
+        .points
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, (val, pos, easing))| {
+            egui::Grid::new(format!("{id}_p_{i}")).show(ui, |ui| {
+                ui.label("x");
+                ui.add(slider(&mut val.x, 0.0..=9999.0).logarithmic(true));
+                if ui.button("Delete").clicked() {
+                    remove.push(i);
+                }
+
+                ui.end_row();
+                ui.label("y");
+                ui.add(slider(&mut val.y, 0.0..=9999.0).logarithmic(true));
+
+                ui.end_row();
+                ui.label("position");
+                let start_pos = if i == 0 { 0.0 } else { curve_pos[i - 1] };
+                let max_pos = if i + 1 >= curve_pos.len() {
+                    1.0
+                } else {
+                    curve_pos[i + 1]
+                };
+                ui.add(slider(pos, (start_pos)..=max_pos));
+                if i != 0 {
+                    easing_select(ui, format!("{id}_easing_{i}"), easing);
+                }
+            });
+
+            ui.separator();
+        });
+
+    remove.drain(..).for_each(|i| {
+        curve.points.remove(i);
+    });
+
+    curve.sort();
+
+    if ui.button("Add Point").clicked() {
+        curve.points.push((Vec2::ONE, 1.0, None));
+    }
+}
+
 fn draw_gradient(
     ui: &mut Ui,
     gradient: &bevy_enoki::prelude::MultiCurve<LinearRgba>,
@@ -231,7 +231,7 @@ fn gizmo(
     };
 
     for transform in query.iter() {
-        match effect.emission_shape {
+        match &effect.emission_shape {
             EmissionShape::Point => {
                 gizmos.circle_2d(
                     transform.translation.xy(),
@@ -242,7 +242,59 @@ fn gizmo(
             EmissionShape::Circle(radius) => {
                 gizmos.circle_2d(
                     transform.translation.xy(),
-                    radius,
+                    *radius,
+                    Color::LinearRgba(LinearRgba::RED),
+                );
+            }
+            EmissionShape::Rect(size) => {
+                gizmos.rect_2d(
+                    Isometry2d::from_translation(transform.translation.xy()),
+                    *size,
+                    Color::LinearRgba(LinearRgba::RED),
+                );
+            }
+            EmissionShape::Ring { inner, outer } => {
+                for radius in [*inner, *outer] {
+                    gizmos.circle_2d(
+                        transform.translation.xy(),
+                        radius,
+                        Color::LinearRgba(LinearRgba::RED),
+                    );
+                }
+            }
+            EmissionShape::Arc { radius, start, end } => {
+                // gizmo arcs are centered on the y axis
+                let isometry = Isometry2d::new(
+                    transform.translation.xy(),
+                    Rot2::radians((start + end) * 0.5 - std::f32::consts::FRAC_PI_2),
+                );
+                gizmos.arc_2d(
+                    isometry,
+                    end - start,
+                    *radius,
+                    Color::LinearRgba(LinearRgba::RED),
+                );
+            }
+            EmissionShape::Line(start, end) => {
+                gizmos.line_2d(
+                    transform.translation.xy() + *start,
+                    transform.translation.xy() + *end,
+                    Color::LinearRgba(LinearRgba::RED),
+                );
+            }
+            EmissionShape::Image(_) | EmissionShape::Mesh(_) => {
+                gizmos.circle_2d(
+                    transform.translation.xy(),
+                    2.0,
+                    Color::LinearRgba(LinearRgba::RED),
+                );
+            }
+            EmissionShape::Polygon(points) => {
+                gizmos.linestrip_2d(
+                    points
+                        .iter()
+                        .chain(points.first())
+                        .map(|point| transform.translation.xy() + *point),
                     Color::LinearRgba(LinearRgba::RED),
                 );
             }
@@ -476,7 +528,7 @@ pub(crate) fn in_game_settings(
                     state.timer.duration().as_secs_f32()
                 ));
             } else if ui.button("Emit particles").clicked() {
-                state.active = true;
+                state.restart();
             }
             let mut speed = time.relative_speed();
             ui.checkbox(&mut settings.repeat_playback, "Play on repeat")
@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use bevy_enoki::prelude::*;
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EnokiPlugin)
+        .add_systems(Startup, setup)
+        .add_plugins(utils::camera_and_ui_plugin)
+        .run();
+}
+
+fn setup(mut cmds: Commands, server: Res<AssetServer>) {
+    cmds.spawn((
+        ParticleSpawner::default(),
+        ParticleEffectHandle(server.load("collision.particle.ron")),
+        Transform::from_xyz(0., 300., 0.),
+    ));
+
+    // the floor, particles collide from above
+    cmds.spawn((ParticleCollider::Plane, Transform::from_xyz(0., -250., 0.)));
+
+    cmds.spawn((
+        ParticleCollider::Circle(60.),
+        Transform::from_xyz(-100., 0., 0.),
+    ));
+
+    cmds.spawn((
+        ParticleCollider::Rect(Vec2::new(80., 20.)),
+        Transform::from_xyz(120., -80., 0.).with_rotation(Quat::from_rotation_z(0.4)),
+    ));
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_enoki::prelude::*;
+use std::f32::consts::PI;
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EnokiPlugin)
+        .add_systems(Startup, setup)
+        .add_plugins(utils::camera_and_ui_plugin)
+        .run();
+}
+
+fn setup(mut cmds: Commands, mut effects: ResMut<Assets<Particle2dEffect>>) {
+    let base = Particle2dEffect {
+        spawn_rate: 0.02,
+        spawn_amount: 10,
+        lifetime: Rval::new(1.0, 0.2),
+        direction: Some(Rval::new(Vec2::X, 0.05)),
+        linear_speed: Some(Rval::new(30., 0.2)),
+        linear_damp: Some(Rval::new(2., 0.)),
+        scale: Some(Rval::new(3., 0.3)),
+        emit_along_normal: Some(true),
+        ..default()
+    };
+
+    let shapes = [
+        (EmissionShape::Circle(60.), EmissionMode::Volume),
+        (
+            EmissionShape::Rect(Vec2::new(140., 80.)),
+            EmissionMode::Edge,
+        ),
+        (
+            EmissionShape::Ring {
+                inner: 40.,
+                outer: 60.,
+            },
+            EmissionMode::Volume,
+        ),
+        (
+            EmissionShape::Arc {
+                radius: 60.,
+                start: 0.,
+                end: PI,
+            },
+            EmissionMode::Edge,
+        ),
+        (
+            EmissionShape::Line(Vec2::new(-60., -30.), Vec2::new(60., 30.)),
+            EmissionMode::Edge,
+        ),
+        (
+            // a concave star
+            EmissionShape::Polygon(
+                (0..10)
+                    .map(|i| {
+                        let radius = if i % 2 == 0 { 70. } else { 30. };
+                        Vec2::from_angle(i as f32 * PI / 5.) * radius
+                    })
+                    .collect(),
+            ),
+            EmissionMode::Volume,
+        ),
+    ];
+
+    for (i, (shape, mode)) in shapes.into_iter().enumerate() {
+        let effect = effects.add(Particle2dEffect {
+            emission_shape: shape,
+            emission_mode: Some(mode),
+            ..base.clone()
+        });
+
+        cmds.spawn((
+            ParticleSpawner::default(),
+            ParticleEffectHandle(effect),
+            Transform::from_xyz(
+                (i % 3) as f32 * 250. - 250.,
+                (i / 3) as f32 * -250. + 125.,
+                0.,
+            ),
+        ));
+    }
+}
@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use bevy_enoki::prelude::*;
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EnokiPlugin)
+        .add_systems(Startup, setup)
+        .add_plugins(utils::camera_and_ui_plugin)
+        .run();
+}
+
+fn setup(mut cmds: Commands, server: Res<AssetServer>) {
+    cmds.spawn((
+        ParticleSpawner::default(),
+        ParticleEffectHandle(server.load("force_field.particle.ron")),
+    ));
+
+    // only affected by fields on the second layer
+    cmds.spawn((
+        ParticleSpawner::default(),
+        ParticleEffectHandle(server.load("force_field.particle.ron")),
+        ParticleForceLayers(0b10),
+        Transform::from_xyz(400., 0., 0.),
+    ));
+
+    // black hole, pulling in everything nearby
+    cmds.spawn((
+        ParticleForceField::point(2000000.).with_radius(250., ForceFalloff::Smooth),
+        Transform::from_xyz(-100., 0., 0.),
+    ));
+
+    cmds.spawn((
+        ParticleForceField::vortex(150.).with_radius(200., ForceFalloff::Linear),
+        Transform::from_xyz(150., 0., 0.),
+    ));
+
+    // a fan, blowing upwards
+    cmds.spawn((
+        ParticleForceField::wind(Vec2::Y, 200.)
+            .with_radius(300., ForceFalloff::Linear)
+            .with_layers(0b10),
+        Transform::from_xyz(400., -200., 0.),
+    ));
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+use bevy_enoki::prelude::*;
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EnokiPlugin)
+        .add_systems(Startup, setup)
+        .add_plugins(utils::camera_and_ui_plugin)
+        .run();
+}
+
+fn setup(mut cmds: Commands, server: Res<AssetServer>) {
+    // the logo dissolves into dust, in the colors of its pixels
+    cmds.spawn((
+        ParticleSpawner::default(),
+        ParticleEffectHandle(server.load("image_mask.particle.ron")),
+    ));
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use bevy_enoki::prelude::*;
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EnokiPlugin)
+        .add_systems(Startup, setup)
+        .add_plugins(utils::camera_and_ui_plugin)
+        .run();
+}
+
+fn setup(
+    mut cmds: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut effects: ResMut<Assets<Particle2dEffect>>,
+) {
+    let base = Particle2dEffect {
+        spawn_rate: 0.02,
+        spawn_amount: 15,
+        lifetime: Rval::new(1.0, 0.3),
+        scale: Some(Rval::new(3., 0.3)),
+        color: Some(LinearRgba::new(0.5, 1.5, 3.0, 1.0)),
+        ..default()
+    };
+
+    // glowing outline around a level boundary
+    let boundary = meshes.add(Annulus::new(150., 160.));
+    cmds.spawn((
+        Mesh2d(boundary.clone()),
+        MeshMaterial2d(materials.add(Color::srgb(0.1, 0.1, 0.2))),
+        Transform::from_xyz(-200., 0., -1.),
+    ));
+    cmds.spawn((
+        ParticleSpawner::default(),
+        ParticleEffectHandle(effects.add(Particle2dEffect {
+            emission_shape: EmissionShape::Mesh(MeshShape {
+                path: None,
+                handle: Some(boundary),
+                scale: Vec2::ONE,
+            }),
+            emission_mode: Some(EmissionMode::Edge),
+            emit_along_normal: Some(true),
+            direction: Some(Rval::new(Vec2::X, 0.1)),
+            linear_speed: Some(Rval::new(20., 0.5)),
+            ..base.clone()
+        })),
+        Transform::from_xyz(-200., 0., 0.),
+    ));
+
+    // filled star, weighted by triangle area
+    let star = meshes.add(RegularPolygon::new(120., 5));
+    cmds.spawn((
+        ParticleSpawner::default(),
+        ParticleEffectHandle(effects.add(Particle2dEffect {
+            emission_shape: EmissionShape::Mesh(MeshShape {
+                path: None,
+                handle: Some(star),
+                scale: Vec2::ONE,
+            }),
+            gravity_direction: Some(Rval::new(Vec2::Y, 0.)),
+            gravity_speed: Some(Rval::new(20., 0.5)),
+            ..base
+        })),
+        Transform::from_xyz(250., 0., 0.),
+    ));
+}
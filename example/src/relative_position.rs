@@ -41,6 +41,16 @@ fn setup(mut cmds: Commands, server: Res<AssetServer>) {
         },
         Transform::from_translation(Vec3::new(-RADIUS, 0.0, 0.0)),
     ));
+
+    // Spawn particle system inheriting the spawner velocity - starts on top
+    cmds.spawn((
+        ParticleSpawner::default(),
+        ParticleEffectHandle(server.load("inherit_velocity.particle.ron")),
+        MovingSpawner {
+            phase_offset: std::f32::consts::FRAC_PI_2, // Starts at 90 degrees (top)
+        },
+        Transform::from_translation(Vec3::new(0.0, RADIUS, 0.0)),
+    ));
 }
 
 fn move_spawners(time: Res<Time>, mut query: Query<(&mut Transform, &MovingSpawner)>) {
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+use bevy_enoki::prelude::*;
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EnokiPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, spin)
+        .add_plugins(utils::camera_and_ui_plugin)
+        .run();
+}
+
+#[derive(Component)]
+struct Spin;
+
+fn setup(mut cmds: Commands, mut effects: ResMut<Assets<Particle2dEffect>>) {
+    // sparks flying off the rim of a wheel
+    let base = Particle2dEffect {
+        spawn_rate: 0.02,
+        spawn_amount: 4,
+        emission_shape: EmissionShape::Circle(80.),
+        emission_mode: Some(EmissionMode::Edge),
+        emit_along_normal: Some(true),
+        lifetime: Rval::new(1.2, 0.2),
+        direction: Some(Rval::new(Vec2::X, 0.05)),
+        linear_speed: Some(Rval::new(40., 0.3)),
+        scale: Some(Rval::new(4., 0.3)),
+        color: Some(LinearRgba::new(3.0, 1.5, 0.5, 1.0)),
+        ..default()
+    };
+
+    let spaces = [
+        SimulationSpace::World,
+        SimulationSpace::Hybrid(0.5),
+        SimulationSpace::Local,
+    ];
+
+    for (i, space) in spaces.into_iter().enumerate() {
+        cmds.spawn((
+            ParticleSpawner::default(),
+            ParticleEffectHandle(effects.add(Particle2dEffect {
+                simulation_space: Some(space),
+                ..base.clone()
+            })),
+            Transform::from_xyz(i as f32 * 300. - 300., 0., 0.),
+            Spin,
+        ));
+    }
+}
+
+fn spin(time: Res<Time>, mut query: Query<&mut Transform, With<Spin>>) {
+    for mut transform in query.iter_mut() {
+        transform.rotate_z(time.delta_secs() * 2.);
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+use bevy_enoki::prelude::*;
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EnokiPlugin)
+        .add_systems(Startup, setup)
+        .add_plugins(utils::camera_and_ui_plugin)
+        .run();
+}
+
+fn setup(mut cmds: Commands, server: Res<AssetServer>) {
+    // every particle trails smoke and bursts into sparks, when it dies
+    cmds.spawn((
+        ParticleSpawner::default(),
+        ParticleEffectHandle(server.load("sub_emitter.particle.ron")),
+    ));
+}
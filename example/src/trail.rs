@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use bevy_enoki::prelude::*;
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EnokiPlugin)
+        .add_systems(Startup, setup)
+        .add_plugins(utils::camera_and_ui_plugin)
+        .run();
+}
+
+fn setup(
+    mut cmds: Commands,
+    mut materials: ResMut<Assets<ColorParticle2dMaterial>>,
+    server: Res<AssetServer>,
+) {
+    let material = materials
+        .add(ColorParticle2dMaterial::default().with_blend_mode(ParticleBlendMode::Additive));
+
+    cmds.spawn((
+        ParticleSpawner(material),
+        ParticleEffectHandle(server.load("trail.particle.ron")),
+    ));
+}